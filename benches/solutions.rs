@@ -0,0 +1,124 @@
+//! Times every generator and solver separately against the puzzle samples in
+//! `benches/samples`, and against real inputs in `input/2023` (or `$AOC_INPUT_DIR`)
+//! when they exist.
+//!
+//! Run a single day with e.g. `cargo bench --bench solutions -- day5/`.
+
+use std::{
+    hint::black_box,
+    path::{Path, PathBuf},
+};
+
+use aoc_rust_2023::*;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+/// Generators either can't fail or return a `Result`, and the solvers want what's inside
+trait Generated {
+    type Output;
+
+    fn generated(self) -> Self::Output;
+}
+
+impl<T> Generated for Vec<T> {
+    type Output = Self;
+
+    fn generated(self) -> Self::Output {
+        self
+    }
+}
+
+impl<T> Generated for color_eyre::Result<T> {
+    type Output = T;
+
+    fn generated(self) -> Self::Output {
+        self.expect("input generates")
+    }
+}
+
+fn manifest_dir() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn real_input_dir() -> PathBuf {
+    std::env::var_os("AOC_INPUT_DIR")
+        .map_or_else(|| manifest_dir().join("input/2023"), PathBuf::from)
+}
+
+/// The sample for `day`, followed by the real input when there is one
+fn inputs(day: u32) -> Vec<(&'static str, String)> {
+    let file = format!("day{day}.txt");
+    let sample = std::fs::read_to_string(manifest_dir().join("benches/samples").join(&file))
+        .expect("every day has a sample");
+
+    let mut inputs = vec![("sample", sample.trim_end().to_owned())];
+    if let Ok(real) = std::fs::read_to_string(real_input_dir().join(&file)) {
+        inputs.push(("real", real.trim_end().to_owned()));
+    }
+
+    inputs
+}
+
+macro_rules! bench_day {
+    // One generator shared by both parts
+    ($c:expr, $day:literal, $generator:path => [$($part:literal: $solver:path),+ $(,)?]) => {{
+        let mut group = $c.benchmark_group(concat!("day", $day));
+        for (name, input) in inputs($day) {
+            group.bench_with_input(BenchmarkId::new("generator", name), &input, |b, input| {
+                b.iter(|| $generator(black_box(input)))
+            });
+
+            let generated = $generator(&input).generated();
+            $(group.bench_with_input(
+                BenchmarkId::new(concat!("part", $part), name),
+                &generated,
+                |b, generated| b.iter(|| $solver(black_box(generated))),
+            );)+
+        }
+        group.finish();
+    }};
+    // A generator of its own for each part
+    ($c:expr, $day:literal, [$($part:literal: $generator:path => $solver:path),+ $(,)?]) => {{
+        let mut group = $c.benchmark_group(concat!("day", $day));
+        for (name, input) in inputs($day) {
+            $(
+                group.bench_with_input(
+                    BenchmarkId::new(concat!("part", $part, " generator"), name),
+                    &input,
+                    |b, input| b.iter(|| $generator(black_box(input))),
+                );
+
+                let generated = $generator(&input).generated();
+                group.bench_with_input(
+                    BenchmarkId::new(concat!("part", $part), name),
+                    &generated,
+                    |b, generated| b.iter(|| $solver(black_box(generated))),
+                );
+            )+
+        }
+        group.finish();
+    }};
+}
+
+fn solutions(c: &mut Criterion) {
+    bench_day!(c, 1, [
+        1: day1::input_generator => day1::part1,
+        2: day1::input_generator_part_2 => day1::part2,
+    ]);
+    bench_day!(c, 2, day2::input_generator => [1: day2::part1, 2: day2::part2]);
+    bench_day!(c, 3, day3::input_generator => [1: day3::part1, 2: day3::part2]);
+    bench_day!(c, 4, day4::input_generator => [1: day4::part1, 2: day4::part2]);
+    bench_day!(c, 5, day5::input_generator => [1: day5::part1, 2: day5::part2]);
+    bench_day!(c, 6, day6::input_generator => [1: day6::part1, 2: day6::part2]);
+    bench_day!(c, 7, [
+        1: day7::part1::input_generator => day7::part1::part1,
+        2: day7::part2::input_generator => day7::part2::part2,
+    ]);
+    bench_day!(c, 8, day8::input_generator => [1: day8::part1, 2: day8::part2]);
+    bench_day!(c, 9, day9::input_generator => [1: day9::part1, 2: day9::part2]);
+    bench_day!(c, 10, day10::input_generator => [1: day10::part1, 2: day10::part2]);
+    bench_day!(c, 11, day11::input_generator => [1: day11::part1, 2: day11::part2]);
+    bench_day!(c, 12, day12::input_generator => [1: day12::part1, 2: day12::part2]);
+}
+
+criterion_group!(benches, solutions);
+criterion_main!(benches);
@@ -0,0 +1,134 @@
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use aoc_rust_2023::runner::{self, Solution};
+use color_eyre::{
+    eyre::{bail, eyre, WrapErr},
+    Result,
+};
+
+const USAGE: &str = "usage: aoc [all | DAY [PART]] [--input PATH]
+
+Runs one day (both parts unless PART is given) against PATH, or stdin when PATH
+is missing or `-`. With `all`, or no day at all, every registered day is run
+against PATH/dayN.txt, where PATH defaults to input/2023.";
+
+enum Selection {
+    All,
+    Day { day: u32, part: Option<u32> },
+}
+
+struct Args {
+    selection: Selection,
+    input: Option<PathBuf>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args> {
+    let mut positional = vec![];
+    let mut input = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                std::process::exit(0);
+            }
+            "-i" | "--input" => {
+                let path = args.next().ok_or_else(|| eyre!("--input needs a path"))?;
+                input = Some(PathBuf::from(path));
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    let selection = match positional.as_slice() {
+        [] => Selection::All,
+        [all] if all == "all" => Selection::All,
+        [day] => Selection::Day {
+            day: day.parse().wrap_err_with(|| format!("invalid day {day}"))?,
+            part: None,
+        },
+        [day, part] => Selection::Day {
+            day: day.parse().wrap_err_with(|| format!("invalid day {day}"))?,
+            part: Some(
+                part.parse()
+                    .wrap_err_with(|| format!("invalid part {part}"))?,
+            ),
+        },
+        _ => bail!("too many arguments\n\n{USAGE}"),
+    };
+
+    Ok(Args { selection, input })
+}
+
+fn read_input(path: Option<&Path>) -> Result<String> {
+    match path {
+        Some(path) if path != Path::new("-") => std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("could not read input from {}", path.display())),
+        _ => {
+            let mut input = String::new();
+            std::io::stdin()
+                .read_to_string(&mut input)
+                .wrap_err("could not read input from stdin")?;
+            Ok(input)
+        }
+    }
+}
+
+fn run(solution: &Solution, input: &str) -> Result<()> {
+    let answer = solution.run(input)?;
+
+    println!(
+        "Day {} - Part {}: {}\n\tgenerator: {:?},\n\trunner: {:?}\n",
+        solution.day, solution.part, answer.value, answer.generator, answer.solver
+    );
+
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    color_eyre::install()?;
+    let args = parse_args(std::env::args().skip(1))?;
+
+    println!("Advent of code 2023");
+
+    match args.selection {
+        Selection::Day { day, part } => {
+            let solutions: Vec<_> = runner::SOLUTIONS
+                .iter()
+                .filter(|solution| solution.day == day && part.is_none_or(|p| p == solution.part))
+                .collect();
+
+            if solutions.is_empty() {
+                bail!("no solution registered for the requested day and part");
+            }
+
+            let input = read_input(args.input.as_deref())?;
+            for solution in solutions {
+                run(solution, &input)?;
+            }
+        }
+        Selection::All => {
+            let dir = args.input.unwrap_or_else(|| PathBuf::from("input/2023"));
+
+            for solution in runner::SOLUTIONS {
+                let path = dir.join(format!("day{}.txt", solution.day));
+                if !path.exists() {
+                    eprintln!(
+                        "Day {} - Part {}: skipped, {} does not exist\n",
+                        solution.day,
+                        solution.part,
+                        path.display()
+                    );
+                    continue;
+                }
+
+                run(solution, &read_input(Some(&path))?)?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,287 +1,387 @@
+use std::sync::Once;
 use std::{
     cmp::min,
-    collections::{HashMap, HashSet},
+    collections::HashMap,
     fmt::{Display, Formatter, Write},
 };
-use std::sync::Once;
 
 use aoc_runner_derive::{aoc, aoc_generator};
 use color_eyre::{
-    eyre::{Context, eyre, OptionExt},
-    Report, Result,
+    eyre::{bail, eyre},
+    Result,
+};
+
+use crate::utils::{
+    self,
+    point::{Direction, Point},
 };
 
 static COLOR_EYRE: Once = Once::new();
 
 #[aoc_generator(day10)]
 pub fn input_generator(input: &str) -> Result<Grid> {
+    // the runner binary installs its own hook before any generator runs
     COLOR_EYRE.call_once(|| {
-        color_eyre::install().unwrap()
+        let _ = color_eyre::install();
     });
-    parsers::parse_input(input).map_err(|e| eyre!(e.to_string()))
+    parsers::parse_input(input)
 }
 
 #[aoc(day10, part1)]
 pub fn part1(input: &Grid) -> Result<u64> {
-    let main_loop = input.main_loop().context("there must be a main loop")?;
-    let (&start_pos, &start_tile) = main_loop
-        .tiles
-        .iter()
-        .find(|(_, tile)| tile.tile_type == TileType::Start)
-        .ok_or_eyre("there must be a start")?;
-
-    let mut distances: HashMap<Pos, u64> = HashMap::new();
-    distances.insert(start_pos, 0);
-
-    let mut flows_from_start = start_tile
-        .neighbors()
-        .into_iter()
-        .filter_map(|pos| main_loop.tiles.get(&pos))
-        .filter(|tile| start_tile.flows(tile));
-
-    let mut seen: HashSet<Pos> = HashSet::new();
-    seen.insert(start_pos);
-    let mut next = flows_from_start.next();
-    let mut distance = 1;
-    while let Some(current) = next {
-        distances.insert(current.pos, distance);
-        seen.insert(current.pos);
-        distance += 1;
-
-        next = current
-            .neighbors()
-            .iter()
-            .filter_map(|pos| main_loop.tiles.get(pos))
-            .find(|tile| current.flows(tile) && !seen.contains(&tile.pos));
-    }
-
-    seen.clear();
-    seen.insert(start_pos);
-    next = flows_from_start.next();
-    distance = 1;
-
-    while let Some(current) = next {
-        let known_distance = distances
-            .get_mut(&current.pos)
-            .ok_or_eyre("tile should have been seen in first loop")?;
-
-        *known_distance = min(distance, *known_distance);
-
-        seen.insert(current.pos);
-        distance += 1;
+    let (_, distance) = input.main_loop()?.farthest();
 
-        next = current
-            .neighbors()
-            .iter()
-            .filter_map(|pos| main_loop.tiles.get(pos))
-            .find(|tile| current.flows(tile) && !seen.contains(&tile.pos));
-    }
-
-    distances
-        .values()
-        .max()
-        .copied()
-        .ok_or_eyre("there must be a max")
+    Ok(distance)
 }
 
 #[aoc(day10, part2)]
 pub fn part2(input: &Grid) -> Result<u64> {
-    let mut main_loop = input.main_loop().context("there must be a main loop")?;
-    let check_loop = main_loop.clone();
-    let (_, start_tile) = main_loop
-        .tiles
-        .iter_mut()
-        .find(|(_, tile)| tile.tile_type == TileType::Start)
-        .ok_or_eyre("there must be a start")?;
-
-    let connectors: Vec<(Direction, TileType)> = start_tile
-        .neighbors()
-        .into_iter()
-        .filter_map(|pos| check_loop.tiles.get(&pos))
-        .filter(|tile| start_tile.flows(tile))
-        .map(|tile| {
-            let offset = tile.pos - start_tile.pos;
-            (Direction::try_from(offset).unwrap(), tile.tile_type)
-        })
-        .collect();
-
-    let s_type = [
-        TileType::SouthWest90,
-        TileType::SouthEast90,
-        TileType::Vertical,
-        TileType::Horizontal,
-        TileType::NorthEast90,
-        TileType::NorthWest90,
-    ]
-        .into_iter()
-        .filter(|possible| {
-            let (dir, tile) = &connectors[0];
-            possible.flows(dir, tile)
-        })
-        .find(|possible| {
-            let (dir, tile) = &connectors[1];
-            possible.flows(dir, tile)
-        })
-        .unwrap();
-
-    start_tile.tile_type = s_type;
-
-    let mut outside = true;
-    let mut count = 0;
-    (0..main_loop.rows).for_each(|row| {
-        (0..main_loop.cols).for_each(|col| {
-            let pos = Pos::new(col as i64, row as i64);
-            let tile_type = main_loop.tiles.get(&pos).unwrap().tile_type;
-
-            if matches!(
-                tile_type,
-                TileType::Vertical | TileType::SouthWest90 | TileType::SouthEast90
-            ) {
-                outside = !outside;
-            }
+    let regions = input.regions(&input.main_loop()?);
+    let inside = regions
+        .iter()
+        .filter(|(_, &region)| region == Region::Inside)
+        .count();
 
-            if !outside && tile_type == TileType::Ground {
-                count += 1;
-            }
-        });
-        outside = true;
-    });
+    Ok(u64::try_from(inside)?)
+}
 
-    Ok(count)
+#[aoc(day10, part2, Shoelace)]
+pub fn part2_shoelace(input: &Grid) -> Result<u64> {
+    input.main_loop()?.enclosed_area()
 }
 
-type Pos = glam::I64Vec2;
+type Pos = Point;
 
+/// A maze of pipes where the start has been replaced by the pipe that fits there
 #[derive(Debug, Clone)]
 pub struct Grid {
-    tiles: HashMap<Pos, Tile>,
-    rows: usize,
-    cols: usize,
+    tiles: utils::grid::Grid<TileType>,
+    start: Pos,
 }
 
+/// Prints the maze as it was given, with the start as `S`
 impl Display for Grid {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        for row in 0..self.rows {
-            for col in 0..self.cols {
-                let tile = self
-                    .tiles
-                    .get(&Pos::new(col as i64, row as i64))
-                    .expect("all points exist on the grid")
-                    .tile_type;
-                write!(f, "{tile}")?;
-            }
-            writeln!(f)?;
-        }
+        let mut tiles = self.tiles.map(ToString::to_string);
+        tiles[self.start] = TileType::Start.to_string();
 
-        Ok(())
+        write!(f, "{tiles}")
     }
 }
 
 impl Grid {
-    fn main_loop(&self) -> Result<Self> {
-        let (&start_pos, &start_tile) = self
-            .tiles
+    /// Finds the start and works out which pipe it stands for from the neighbours that
+    /// connect to it.
+    ///
+    /// # Errors
+    ///
+    /// Fails unless there is exactly one start with exactly two neighbours connecting to it.
+    fn new(mut tiles: utils::grid::Grid<TileType>) -> Result<Self> {
+        let starts: Vec<Pos> = tiles
             .iter()
-            .find(|(_, tile)| tile.tile_type == TileType::Start)
-            .ok_or_eyre("grid does not have a start position")?;
+            .filter(|(_, &tile_type)| tile_type == TileType::Start)
+            .map(|(pos, _)| pos)
+            .collect();
+        let start = match starts[..] {
+            [start] => start,
+            [] => bail!("grid does not have a start position"),
+            _ => bail!("grid has {} start positions, expected one", starts.len()),
+        };
+
+        let connections: Vec<Direction> = Direction::ALL
+            .into_iter()
+            .filter(|&direction| {
+                tiles
+                    .get(start + direction.offset())
+                    .is_some_and(|other| TileType::Start.flows(&direction, other))
+            })
+            .collect();
 
-        let mut main_loop: HashMap<Pos, Tile> = HashMap::new();
-        main_loop.insert(start_pos, start_tile);
+        tiles[start] = TileType::joining(&connections).ok_or_else(|| {
+            eyre!(
+                "start at ({}, {}) connects to {} neighbours {connections:?}, but a pipe joins exactly two",
+                start.x,
+                start.y,
+                connections.len(),
+            )
+        })?;
 
-        let mut next = start_tile
-            .neighbors()
-            .iter()
-            .filter_map(|pos| self.tiles.get(pos))
-            .find(|tile| start_tile.flows(tile) && !main_loop.contains_key(&tile.pos));
+        Ok(Self { tiles, start })
+    }
 
-        while let Some(current) = next {
-            main_loop.insert(current.pos, *current);
+    /// Where `S` was in the maze
+    #[must_use]
+    pub fn start_position(&self) -> Pos {
+        self.start
+    }
 
+    fn tile(&self, pos: Pos) -> Option<Tile> {
+        self.tiles
+            .get(pos)
+            .map(|&tile_type| Tile { tile_type, pos })
+    }
+
+    fn start(&self) -> Tile {
+        Tile {
+            tile_type: self.tiles[self.start],
+            pos: self.start,
+        }
+    }
+
+    /// Walks the loop through the start once, in order
+    ///
+    /// # Errors
+    ///
+    /// Fails when the pipes from the start lead to a dead end instead of back round to it.
+    pub fn main_loop(&self) -> Result<MainLoop> {
+        let start_tile = self.start();
+
+        let mut indices: HashMap<Pos, usize> = HashMap::from([(start_tile.pos, 0)]);
+        let mut positions = vec![start_tile.pos];
+        let mut next = Some(start_tile);
+        let mut last = start_tile;
+
+        while let Some(current) = next {
             next = current
                 .neighbors()
-                .iter()
-                .filter_map(|pos| self.tiles.get(pos))
-                .find(|tile| current.flows(tile) && !main_loop.contains_key(&tile.pos));
+                .into_iter()
+                .filter_map(|pos| self.tile(pos))
+                .find(|tile| current.flows(tile) && !indices.contains_key(&tile.pos));
+
+            if let Some(tile) = next {
+                indices.insert(tile.pos, positions.len());
+                positions.push(tile.pos);
+                last = tile;
+            }
         }
 
-        (0..self.rows).for_each(|row| {
-            (0..self.cols).for_each(|col| {
-                let pos = Pos::new(col as i64, row as i64);
-                let _ = main_loop.entry(pos).or_insert_with(|| Tile {
-                    tile_type: TileType::Ground,
-                    pos,
-                });
-            })
-        });
+        // The first step always leads straight back to the start, which is not a loop
+        let closed = positions.len() > 2
+            && last.neighbors().contains(&start_tile.pos)
+            && last.flows(&start_tile);
+        if !closed {
+            bail!(
+                "the pipes from the start at ({}, {}) end at ({}, {}) instead of looping back",
+                start_tile.pos.x,
+                start_tile.pos.y,
+                last.pos.x,
+                last.pos.y,
+            );
+        }
 
-        Ok(Grid {
-            tiles: main_loop,
-            ..*self
-        })
+        Ok(MainLoop { positions, indices })
+    }
+
+    /// The positions of the loop through the start in the order they are walked, starting
+    /// with the start itself
+    ///
+    /// # Errors
+    ///
+    /// Fails when there is no loop through the start.
+    pub fn loop_positions(&self) -> Result<Vec<Pos>> {
+        Ok(self.main_loop()?.positions)
+    }
+
+    /// Sorts every tile into the loop, or inside or outside of it. Scanning along a row, a
+    /// tile is inside once an odd number of loop pipes reaching south have been crossed.
+    #[must_use]
+    pub fn regions(&self, main_loop: &MainLoop) -> utils::grid::Grid<Region> {
+        let mut regions = self.tiles.map(|_| Region::Outside);
+        let mut inside = false;
+
+        for (pos, &tile_type) in self.tiles.iter() {
+            if pos.x == 0 {
+                inside = false;
+            }
+
+            if main_loop.contains(pos) {
+                regions[pos] = Region::Loop;
+                if tile_type.reaches(Direction::South) {
+                    inside = !inside;
+                }
+            } else if inside {
+                regions[pos] = Region::Inside;
+            }
+        }
+
+        regions
+    }
+
+    /// Draws the maze with box-drawing characters, coloured for a terminal
+    #[must_use]
+    pub fn render(&self) -> Render<'_> {
+        Render {
+            grid: self,
+            colour: true,
+        }
+    }
+
+    /// A copy of the maze with everything but `main_loop` cleared to ground
+    #[must_use]
+    pub fn loop_only(&self, main_loop: &MainLoop) -> Self {
+        let mut tiles = self.tiles.map(|_| TileType::Ground);
+        for &pos in main_loop.positions() {
+            tiles[pos] = self.tiles[pos];
+        }
+
+        Grid {
+            tiles,
+            start: self.start,
+        }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Tile {
-    tile_type: TileType,
-    pos: Pos,
+/// The loop through the start of a maze, in the order it is walked from the start
+#[derive(Debug, Clone, Default)]
+pub struct MainLoop {
+    positions: Vec<Pos>,
+    indices: HashMap<Pos, usize>,
 }
 
-impl Tile {
-    fn neighbors(&self) -> Vec<Pos> {
-        [
-            Direction::North,
-            Direction::South,
-            Direction::East,
-            Direction::West,
-        ]
+impl MainLoop {
+    /// Every position on the loop, starting with the start
+    #[must_use]
+    pub fn positions(&self) -> &[Pos] {
+        &self.positions
+    }
+
+    #[must_use]
+    pub fn contains(&self, pos: Pos) -> bool {
+        self.indices.contains_key(&pos)
+    }
+
+    /// How many steps `pos` is from the start going whichever way round the loop is shorter,
+    /// or `None` when it is not on the loop
+    #[must_use]
+    pub fn distance(&self, pos: Pos) -> Option<u64> {
+        self.indices.get(&pos).map(|&index| self.distance_at(index))
+    }
+
+    /// Every position on the loop alongside its distance from the start, in loop order
+    pub fn distances(&self) -> impl Iterator<Item = (Pos, u64)> + '_ {
+        self.positions
             .iter()
-            .map(|dir| dir.offset())
-            .map(|offset| self.pos + offset)
-            .collect()
+            .enumerate()
+            .map(|(index, &pos)| (pos, self.distance_at(index)))
     }
 
-    fn flows(&self, other: &Tile) -> bool {
-        let offset = other.pos - self.pos;
-        let direction: Direction = offset.try_into().expect("valid offset");
+    /// The position furthest from the start, halfway round the loop
+    #[must_use]
+    pub fn farthest(&self) -> (Pos, u64) {
+        let index = self.positions.len() / 2;
 
-        self.tile_type.flows(&direction, &other.tile_type)
+        (self.positions[index], self.distance_at(index))
     }
+
+    /// The number of tiles enclosed by the loop. The shoelace formula gives the area of
+    /// the polygon through the loop's tile centres, and Pick's theorem turns that into the
+    /// count of whole tiles strictly inside it.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the loop is too long to count.
+    pub fn enclosed_area(&self) -> Result<u64> {
+        let positions = &self.positions;
+
+        let twice_area = positions
+            .iter()
+            .zip(positions.iter().cycle().skip(1))
+            .map(|(a, b)| a.x * b.y - b.x * a.y)
+            .sum::<i64>()
+            .unsigned_abs();
+        let boundary = u64::try_from(positions.len())?;
+
+        // A = I + B / 2 - 1
+        Ok((twice_area + 2).saturating_sub(boundary) / 2)
+    }
+
+    fn distance_at(&self, index: usize) -> u64 {
+        let steps = min(index, self.positions.len() - index);
+
+        u64::try_from(steps).expect("loop fits in memory")
+    }
+}
+
+/// Where a tile is relative to the main loop
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Region {
+    Loop,
+    Inside,
+    Outside,
 }
 
-enum Direction {
-    North,
-    South,
-    East,
-    West,
+/// A maze drawn with box-drawing characters. The main loop is drawn in heavy lines and
+/// everything else in light ones, with the loop, the start and the tiles inside and outside
+/// of the loop each in their own colour unless the rendering is [`plain`](Render::plain).
+pub struct Render<'a> {
+    grid: &'a Grid,
+    colour: bool,
 }
 
-impl Direction {
-    fn offset(&self) -> Pos {
-        match self {
-            Direction::North => Pos::new(0, -1),
-            Direction::South => Pos::new(0, 1),
-            Direction::East => Pos::new(1, 0),
-            Direction::West => Pos::new(-1, 0),
+impl Render<'_> {
+    /// Leaves out the ANSI colours, for output that isn't going to a terminal
+    #[must_use]
+    pub fn plain(self) -> Self {
+        Self {
+            colour: false,
+            ..self
         }
     }
 }
 
-impl TryFrom<Pos> for Direction {
-    type Error = Report;
+impl Display for Render<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // A maze without a loop is drawn as if everything were outside of it
+        let main_loop = self.grid.main_loop().unwrap_or_default();
+        let regions = self.grid.regions(&main_loop);
+
+        for (pos, &region) in regions.iter() {
+            if pos.x == 0 && pos.y > 0 {
+                writeln!(f)?;
+            }
 
-    fn try_from(value: Pos) -> std::result::Result<Self, Self::Error> {
-        if !(value.x.abs() == 1 && value.y == 0 || value.y.abs() == 1 && value.x == 0) {
-            return Err(eyre!("offset is more than one step away or diagonal"));
+            let glyph = self.grid.tiles[pos].glyph(region == Region::Loop);
+            if self.colour {
+                let colour = match region {
+                    Region::Loop if pos == self.grid.start => ansi::START,
+                    Region::Loop => ansi::LOOP,
+                    Region::Inside => ansi::INSIDE,
+                    Region::Outside => ansi::OUTSIDE,
+                };
+                write!(f, "{colour}{glyph}{}", ansi::RESET)?;
+            } else {
+                f.write_char(glyph)?;
+            }
         }
 
-        Ok(match value {
-            Pos { x: 0, y: -1 } => Direction::North,
-            Pos { x: 0, y: 1 } => Direction::South,
-            Pos { x: 1, y: 0 } => Direction::East,
-            Pos { x: -1, y: 0 } => Direction::West,
-            _ => unreachable!(),
-        })
+        writeln!(f)
+    }
+}
+
+mod ansi {
+    pub(super) const LOOP: &str = "\x1b[1;33m";
+    pub(super) const START: &str = "\x1b[1;31m";
+    pub(super) const INSIDE: &str = "\x1b[32m";
+    pub(super) const OUTSIDE: &str = "\x1b[2;34m";
+    pub(super) const RESET: &str = "\x1b[0m";
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Tile {
+    tile_type: TileType,
+    pos: Pos,
+}
+
+impl Tile {
+    fn neighbors(&self) -> Vec<Pos> {
+        self.pos.cardinal_neighbors().to_vec()
+    }
+
+    fn flows(&self, other: &Tile) -> bool {
+        let offset = other.pos - self.pos;
+        let direction: Direction = offset.try_into().expect("valid offset");
+
+        self.tile_type.flows(&direction, &other.tile_type)
     }
 }
 
@@ -298,6 +398,60 @@ enum TileType {
 }
 
 impl TileType {
+    fn reaches(self, direction: Direction) -> bool {
+        match direction {
+            Direction::North => matches!(
+                self,
+                TileType::Vertical | TileType::NorthEast90 | TileType::NorthWest90
+            ),
+            Direction::East => matches!(
+                self,
+                TileType::Horizontal | TileType::NorthEast90 | TileType::SouthEast90
+            ),
+            Direction::South => matches!(
+                self,
+                TileType::Vertical | TileType::SouthWest90 | TileType::SouthEast90
+            ),
+            Direction::West => matches!(
+                self,
+                TileType::Horizontal | TileType::NorthWest90 | TileType::SouthWest90
+            ),
+        }
+    }
+
+    /// The box-drawing character for this tile, in heavy lines when it is `on_loop`
+    fn glyph(self, on_loop: bool) -> char {
+        match (self, on_loop) {
+            (TileType::Vertical, false) => '│',
+            (TileType::Vertical, true) => '┃',
+            (TileType::Horizontal, false) => '─',
+            (TileType::Horizontal, true) => '━',
+            (TileType::NorthEast90, false) => '└',
+            (TileType::NorthEast90, true) => '┗',
+            (TileType::NorthWest90, false) => '┘',
+            (TileType::NorthWest90, true) => '┛',
+            (TileType::SouthWest90, false) => '┐',
+            (TileType::SouthWest90, true) => '┓',
+            (TileType::SouthEast90, false) => '┌',
+            (TileType::SouthEast90, true) => '┏',
+            (TileType::Ground, _) => '·',
+            (TileType::Start, _) => 'S',
+        }
+    }
+
+    /// The pipe joining exactly the two `directions`, given clockwise from north
+    fn joining(directions: &[Direction]) -> Option<Self> {
+        match directions {
+            [Direction::North, Direction::East] => Some(TileType::NorthEast90),
+            [Direction::North, Direction::South] => Some(TileType::Vertical),
+            [Direction::North, Direction::West] => Some(TileType::NorthWest90),
+            [Direction::East, Direction::South] => Some(TileType::SouthEast90),
+            [Direction::East, Direction::West] => Some(TileType::Horizontal),
+            [Direction::South, Direction::West] => Some(TileType::SouthWest90),
+            _ => None,
+        }
+    }
+
     fn flows(&self, direction: &Direction, other: &TileType) -> bool {
         match self {
             TileType::Vertical => match direction {
@@ -405,51 +559,26 @@ impl Display for TileType {
 }
 
 mod parsers {
-    use std::collections::HashMap;
-
+    use color_eyre::eyre::eyre;
     use nom::{
         character::complete::{line_ending, one_of},
-        IResult,
-        multi::{many1, separated_list1}, Parser,
+        multi::{many1, separated_list1},
+        IResult, Parser,
     };
     use nom_locate::LocatedSpan;
     use nom_supreme::{error::ErrorTree, final_parser::final_parser};
 
-    use crate::day10::{Grid, Pos, Tile, TileType};
+    use crate::day10::{Grid, TileType};
 
     type ParseError<'a> = ErrorTree<Span<'a>>;
 
     type Span<'a> = LocatedSpan<&'a str>;
 
-    pub(crate) fn parse_input(input: &str) -> color_eyre::Result<Grid, ParseError> {
-        let tiles = final_parser(tile_types)(Span::new(input))?;
-
-        let map: HashMap<Pos, Tile> = tiles
-            .iter()
-            .enumerate()
-            .flat_map(|(row, line)| {
-                line.iter()
-                    .enumerate()
-                    .map(|(col, tile)| {
-                        let pos = Pos::new(col as i64, row as i64);
-
-                        (
-                            pos,
-                            Tile {
-                                tile_type: *tile,
-                                pos,
-                            },
-                        )
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .collect();
+    pub(crate) fn parse_input(input: &str) -> color_eyre::Result<Grid> {
+        let tiles = final_parser(tile_types)(Span::new(input))
+            .map_err(|e: ParseError| eyre!(e.to_string()))?;
 
-        Ok(Grid {
-            tiles: map,
-            rows: tiles.len(),
-            cols: tiles.first().expect("there is a first row").len(),
-        })
+        Grid::new(tiles.try_into()?)
     }
 
     fn tile_types(input: Span) -> IResult<Span, Vec<Vec<TileType>>, ParseError> {
@@ -550,7 +679,7 @@ mod tests {
     })]
     fn test_find_main_loop(#[case] input: &str, #[case] expected_loop: &str) -> Result<()> {
         let parsed_grid = input_generator(input)?;
-        let main_loop_grid = parsed_grid.main_loop()?;
+        let main_loop_grid = parsed_grid.loop_only(&parsed_grid.main_loop()?);
         assert_eq!(expected_loop, main_loop_grid.to_string().trim_end());
         Ok(())
     }
@@ -563,6 +692,20 @@ mod tests {
      .L-J.
      ....."
     }, 4)]
+    #[case::simple_map_with_junk(indoc ! {
+    "-L|F7
+     7S-7|
+     L|7||
+     -L-J|
+     L|-JF"
+    }, 4)]
+    #[case::complex_map(indoc ! {
+    "..F7.
+     .FJ|.
+     SJ.L7
+     |F--J
+     LJ..."
+    }, 8)]
     #[case::more_complex_main_loop(indoc ! {
     "7-F7-
      .FJ|7
@@ -609,6 +752,18 @@ mod tests {
      .L--JL--J.
      .........."
     }, 4)]
+    #[case::larger_example(indoc ! {
+    ".F----7F7F7F7F-7....
+     .|F--7||||||||FJ....
+     .||.FJ||||||||L7....
+     FJL7L7LJLJ||LJ.L-7..
+     L--J.L7...LJS7F-7L7.
+     ....F-J..F7FJ|L7L7L7
+     ....L7.F7||L7|.L7L7|
+     .....|FJLJ|FJ|F7|.LJ
+     ....FJL-7.||.||||...
+     ....L---J.LJ.LJLJ..."
+    }, 8)]
     #[case::insane_case(indoc ! {
     "FF7FSF7F7F7F7F7F---7
      L|LJ||||||||||||F--J
@@ -626,7 +781,149 @@ mod tests {
         let area = part2(&parsed_grid)?;
 
         assert_eq!(expected_area, area);
+        assert_eq!(expected_area, part2_shoelace(&parsed_grid)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_loop_positions() -> Result<()> {
+        let parsed_grid = input_generator(indoc! {
+        "-L|F7
+         7S-7|
+         L|7||
+         -L-J|
+         L|-JF"
+        })?;
+
+        let positions = parsed_grid.loop_positions()?;
+
+        assert_eq!(
+            [
+                (1, 1),
+                (2, 1),
+                (3, 1),
+                (3, 2),
+                (3, 3),
+                (2, 3),
+                (1, 3),
+                (1, 2)
+            ]
+            .map(Pos::from),
+            positions[..]
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::simple_map(".....\n.S-7.\n.|.|.\n.L-J.\n.....", (1, 1), TileType::SouthEast90)]
+    #[case::more_complex_main_loop("..F7.\n.FJ|.\nSJ.L7\n|F--J\nLJ...", (0, 2), TileType::SouthEast90)]
+    #[case::horizontal("-S-", (1, 0), TileType::Horizontal)]
+    #[case::north_west(".|.\n-S.", (1, 1), TileType::NorthWest90)]
+    fn test_resolve_start(
+        #[case] input: &str,
+        #[case] start: (i64, i64),
+        #[case] expected: TileType,
+    ) -> Result<()> {
+        let parsed_grid = input_generator(input)?;
+
+        assert_eq!(Pos::from(start), parsed_grid.start_position());
+        assert_eq!(expected, parsed_grid.start().tile_type);
+        assert_eq!(input, parsed_grid.to_string().trim_end());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::no_start("-7\n-J", "grid does not have a start position")]
+    #[case::two_starts("S-S", "grid has 2 start positions, expected one")]
+    #[case::isolated(
+        ".|.\n.S.",
+        "start at (1, 1) connects to 1 neighbours [North], but a pipe joins exactly two"
+    )]
+    #[case::crossroads(
+        ".|.\n-S-\n.|.",
+        "start at (1, 1) connects to 4 neighbours [North, East, South, West], but a pipe joins exactly two"
+    )]
+    fn test_resolve_start_errors(#[case] input: &str, #[case] expected: &str) {
+        let error = input_generator(input).unwrap_err();
+
+        assert_eq!(expected, error.to_string());
+    }
+
+    #[test]
+    fn test_loop_distances() -> Result<()> {
+        let parsed_grid = input_generator(indoc! {
+        "7-F7-
+         .FJ|7
+         SJLL7
+         |F--J
+         LJ.LJ"
+        })?;
+        let main_loop = parsed_grid.main_loop()?;
+
+        let mut distances = parsed_grid.tiles.map(|_| ".".to_string());
+        for (pos, distance) in main_loop.distances() {
+            distances[pos] = distance.to_string();
+        }
+
+        assert_eq!(
+            indoc! {
+            "..45.
+             .236.
+             01.78
+             14567
+             23..."
+            },
+            distances.to_string().trim_end()
+        );
+        assert_eq!((Pos::new(4, 2), 8), main_loop.farthest());
+        assert_eq!(Some(6), main_loop.distance(Pos::new(3, 3)));
+        assert_eq!(None, main_loop.distance(Pos::new(0, 0)));
+        assert!(!main_loop.contains(Pos::new(2, 2)));
+        Ok(())
+    }
+
+    #[test]
+    fn open_path_is_not_a_loop() -> Result<()> {
+        let parsed_grid = input_generator("S-7\n|.|\nL-.")?;
+
+        assert_eq!(
+            "the pipes from the start at (0, 0) end at (2, 1) instead of looping back",
+            parsed_grid.main_loop().unwrap_err().to_string()
+        );
+        assert!(part1(&parsed_grid).is_err());
+        assert!(part2(&parsed_grid).is_err());
+        assert!(part2_shoelace(&parsed_grid).is_err());
+        assert_eq!("┌─┐\n│·│\n└─·\n", parsed_grid.render().plain().to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn test_render() -> Result<()> {
+        let parsed_grid = input_generator(indoc! {
+        "-L|F7
+         7S-7|
+         L|7||
+         -L-J|
+         L|-JF"
+        })?;
+
+        assert_eq!(
+            indoc! {
+            "─└│┌┐
+             ┐┏━┓│
+             └┃┐┃│
+             ─┗━┛│
+             └│─┘┌
+            "
+            },
+            parsed_grid.render().plain().to_string()
+        );
 
+        let coloured = parsed_grid.render().to_string();
+        assert!(coloured.contains(&format!("{}┏{}", ansi::START, ansi::RESET)));
+        assert!(coloured.contains(&format!("{}┐{}", ansi::INSIDE, ansi::RESET)));
+        assert!(coloured.contains(&format!("{}─{}", ansi::OUTSIDE, ansi::RESET)));
         Ok(())
     }
 }
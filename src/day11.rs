@@ -1,36 +1,29 @@
-use crate::utils::point::Point;
+use crate::utils::{grid::Grid, point::Point};
 use aoc_runner_derive::{aoc, aoc_generator};
-use color_eyre::{eyre::eyre, Result};
+use color_eyre::{eyre::OptionExt, Result};
 use itertools::Itertools;
 use once_cell::sync::Lazy;
-use std::fmt::{Display, Formatter, Write};
+use std::{
+    cmp::Reverse,
+    fmt::{Display, Formatter, Write},
+};
 
 static COLOR_EYRE: Lazy<()> = Lazy::new(|| color_eyre::install().unwrap());
 
 #[aoc_generator(day11)]
 pub fn input_generator(input: &str) -> Result<CosmicMap> {
     Lazy::get(&COLOR_EYRE);
-    parsers::parse_input(input).map_err(|e| eyre!(e.to_string()))
+    parsers::parse_input(input)
 }
 
 #[aoc(day11, part1)]
 pub fn part1(input: &CosmicMap) -> Result<u64> {
-    let input = input.clone();
-    Ok(inner(input, 2))
+    Ok(u64::try_from(input.distance_sum(2)?)?)
 }
 
 #[aoc(day11, part2)]
 pub fn part2(input: &CosmicMap) -> Result<u64> {
-    let input = input.clone();
-    Ok(inner(input, 1_000_000))
-}
-
-fn inner(mut input: CosmicMap, expansion: u64) -> u64 {
-    input.expand(expansion);
-    let poses = input.galaxy_positions();
-    let pairs = generate_pairs(poses);
-
-    pairs.into_iter().map(|pair| pair_distance(pair)).sum()
+    Ok(u64::try_from(input.distance_sum(1_000_000)?)?)
 }
 
 #[derive(Debug, Clone)]
@@ -41,7 +34,7 @@ enum CosmicEntry {
 
 #[derive(Debug, Clone)]
 pub struct CosmicMap {
-    entries: Vec<Vec<CosmicEntry>>,
+    entries: Grid<CosmicEntry>,
 }
 
 #[derive(Debug, Clone)]
@@ -50,14 +43,36 @@ pub struct GalaxyPosition {
     position: Point,
 }
 
-impl Display for CosmicMap {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for row in &self.entries {
-            let row = row.iter().join("");
-            writeln!(f, "{}", row)?;
+/// Where a galaxy ends up once the universe has expanded. Wide enough that every
+/// empty row and column can grow by any `u64` factor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ExpandedPosition {
+    pub id: u64,
+    pub x: u128,
+    pub y: u128,
+}
+
+/// Two galaxies and the length of the shortest path between them, ordered by distance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct GalaxyPair {
+    pub distance: u128,
+    pub from: ExpandedPosition,
+    pub to: ExpandedPosition,
+}
+
+impl GalaxyPair {
+    fn new((from, to): (ExpandedPosition, ExpandedPosition)) -> Self {
+        Self {
+            distance: from.x.abs_diff(to.x) + from.y.abs_diff(to.y),
+            from,
+            to,
         }
+    }
+}
 
-        Ok(())
+impl Display for CosmicMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.entries)
     }
 }
 
@@ -71,92 +86,139 @@ impl Display for CosmicEntry {
 }
 
 impl CosmicMap {
-    fn expand(&mut self, expansion: u64) {
-        let empty_rows: Vec<_> = (0..self.entries.len())
-            .filter(|row| self.is_empty_row(*row))
-            .collect();
-
-        let number_cols = self.entries.first().unwrap().len();
-        let empty_cols: Vec<_> = (0..number_cols)
-            .filter(|col| self.is_empty_column(*col))
-            .collect();
-
-        empty_rows.iter().fold(0, |acc, row| {
-            let mut acc = acc;
-            for _ in 1..expansion {
-                self.insert_row(row + acc);
-                acc += 1;
-            }
-            acc
-        });
-        empty_cols.iter().fold(0, |acc, col| {
-            let mut acc = acc;
-            for _ in 1..expansion {
-                self.insert_column(col + acc);
-                acc += 1;
-            }
-            acc
-        });
+    /// The sum of the shortest distances between every pair of galaxies once each empty
+    /// row and column has been replaced by `expansion` of them.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the sum does not fit in a `u128`.
+    pub fn distance_sum(&self, expansion: u64) -> Result<u128> {
+        let positions = self.expanded_positions(expansion);
+        let xs = positions.iter().map(|galaxy| galaxy.x).collect();
+        let ys = positions.iter().map(|galaxy| galaxy.y).collect();
+
+        axis_distance_sum(xs)?
+            .checked_add(axis_distance_sum(ys)?)
+            .ok_or_eyre("distance sum overflows a u128")
+    }
+
+    /// The `k` closest pairs of galaxies, closest first
+    #[must_use]
+    pub fn nearest_pairs(&self, expansion: u64, k: usize) -> Vec<GalaxyPair> {
+        self.pairs(expansion).k_smallest(k).collect()
+    }
+
+    /// The `k` pairs of galaxies furthest apart, furthest first
+    #[must_use]
+    pub fn farthest_pairs(&self, expansion: u64, k: usize) -> Vec<GalaxyPair> {
+        self.pairs(expansion)
+            .map(|pair| (Reverse(pair.distance), pair))
+            .k_smallest(k)
+            .map(|(_, pair)| pair)
+            .collect()
+    }
+
+    /// Every pair of galaxies, generated as they are needed rather than all up front
+    fn pairs(&self, expansion: u64) -> impl Iterator<Item = GalaxyPair> {
+        self.expanded_positions(expansion)
+            .into_iter()
+            .tuple_combinations()
+            .map(GalaxyPair::new)
+    }
+
+    /// Every galaxy moved out by the empty rows above it and the empty columns to its left,
+    /// each of which now counts `expansion` times
+    ///
+    /// # Panics
+    ///
+    /// Only if a galaxy were somehow off the grid.
+    #[must_use]
+    pub fn expanded_positions(&self, expansion: u64) -> Vec<ExpandedPosition> {
+        let empty_rows_before =
+            Self::empty_before((0..self.entries.height()).map(|row| self.is_empty_row(row)));
+        let empty_cols_before =
+            Self::empty_before((0..self.entries.width()).map(|col| self.is_empty_column(col)));
+        let expand = |coordinate: i64, empty_before: &[u64]| {
+            let coordinate = u64::try_from(coordinate).expect("galaxies are on the grid");
+            let empty =
+                empty_before[usize::try_from(coordinate).expect("galaxies are on the grid")];
+
+            u128::from(coordinate - empty) + u128::from(empty) * u128::from(expansion)
+        };
+
+        self.galaxy_positions()
+            .into_iter()
+            .map(|galaxy| ExpandedPosition {
+                id: galaxy.id,
+                x: expand(galaxy.position.x, &empty_cols_before),
+                y: expand(galaxy.position.y, &empty_rows_before),
+            })
+            .collect()
+    }
+
+    /// How many of the lines before each line are empty
+    fn empty_before(empty: impl Iterator<Item = bool>) -> Vec<u64> {
+        empty
+            .scan(0, |count, is_empty| {
+                let before = *count;
+                *count += u64::from(is_empty);
+                Some(before)
+            })
+            .collect()
     }
 
     fn galaxy_positions(&self) -> Vec<GalaxyPosition> {
-        let mut positions = vec![];
-        for (y, row) in self.entries.iter().enumerate() {
-            for (x, col) in row.iter().enumerate() {
-                if let CosmicEntry::Galaxy(id) = col {
-                    positions.push(GalaxyPosition {
-                        id: *id,
-                        position: Point::from((x as i64, y as i64)),
-                    });
-                }
-            }
-        }
-        positions
+        self.entries
+            .iter()
+            .filter_map(|(position, entry)| match entry {
+                CosmicEntry::Galaxy(id) => Some(GalaxyPosition { id: *id, position }),
+                CosmicEntry::Space => None,
+            })
+            .collect()
     }
 
     fn is_empty_column(&self, col: usize) -> bool {
         self.entries
-            .iter()
-            .map(|row| row.get(col).expect("bounds are checked before usage"))
+            .column(col)
             .all(|entry| matches!(entry, CosmicEntry::Space))
     }
 
     fn is_empty_row(&self, row: usize) -> bool {
         self.entries
-            .get(row)
+            .row(row)
             .expect("bounds are checked before usage")
             .iter()
             .all(|entry| matches!(entry, CosmicEntry::Space))
     }
-
-    fn insert_row(&mut self, after_row: usize) {
-        let col_count = self.entries.first().unwrap().len();
-        let empty = vec![CosmicEntry::Space; col_count];
-        self.entries.insert(after_row + 1, empty);
-    }
-
-    fn insert_column(&mut self, after_col: usize) {
-        for row in self.entries.iter_mut() {
-            row.insert(after_col + 1, CosmicEntry::Space);
-        }
-    }
-}
-
-fn generate_pairs(galaxies: Vec<GalaxyPosition>) -> Vec<(GalaxyPosition, GalaxyPosition)> {
-    galaxies
-        .into_iter()
-        .combinations(2)
-        .map(|arr| (arr.first().unwrap().clone(), arr.get(1).unwrap().clone()))
-        .collect()
 }
 
-fn pair_distance(pair: (GalaxyPosition, GalaxyPosition)) -> u64 {
-    let (left, right) = pair;
+/// The sum of the distances between every pair of `values`. Once sorted, each value is
+/// at least as large as all of those before it, so it is `count * value - before` away
+/// from them in total.
+fn axis_distance_sum(mut values: Vec<u128>) -> Result<u128> {
+    values.sort_unstable();
+
+    let mut before = 0u128;
+    let mut sum = 0u128;
+    for (count, value) in values.into_iter().enumerate() {
+        let distances = u128::try_from(count)?
+            .checked_mul(value)
+            .and_then(|total| total.checked_sub(before))
+            .ok_or_eyre("distance sum overflows a u128")?;
+
+        sum = sum
+            .checked_add(distances)
+            .ok_or_eyre("distance sum overflows a u128")?;
+        before = before
+            .checked_add(value)
+            .ok_or_eyre("coordinate sum overflows a u128")?;
+    }
 
-    left.position.manhattan_distance(&right.position)
+    Ok(sum)
 }
 
 mod parsers {
+    use color_eyre::eyre::eyre;
     use nom::{
         character::complete::{line_ending, one_of},
         multi::{many1, separated_list1},
@@ -171,11 +233,16 @@ mod parsers {
 
     type Span<'a> = LocatedSpan<&'a str>;
 
-    pub(crate) fn parse_input(input: &str) -> color_eyre::Result<CosmicMap, ParseError> {
-        final_parser(cosmic_map)(Span::new(input))
+    pub(crate) fn parse_input(input: &str) -> color_eyre::Result<CosmicMap> {
+        let entries = final_parser(cosmic_map)(Span::new(input))
+            .map_err(|e: ParseError| eyre!(e.to_string()))?;
+
+        Ok(CosmicMap {
+            entries: entries.try_into()?,
+        })
     }
 
-    fn cosmic_map(input: Span) -> IResult<Span, CosmicMap, ParseError> {
+    fn cosmic_map(input: Span) -> IResult<Span, Vec<Vec<CosmicEntry>>, ParseError> {
         let mut next_galaxy_id = 0;
         separated_list1(line_ending, map_line)
             .map(|rows| {
@@ -196,7 +263,6 @@ mod parsers {
                     })
                     .collect()
             })
-            .map(|entries| CosmicMap { entries })
             .parse(input)
     }
 
@@ -260,11 +326,9 @@ mod tests {
              #....#......."
         };
         let expected = input_generator(expected_map)?;
-        let mut input = input_generator(SAMPLE_INPUT)?;
-
-        input.expand(2);
+        let input = input_generator(SAMPLE_INPUT)?;
 
-        assert_eq!(format!("{input}"), format!("{expected}"));
+        assert_eq!(expected.expanded_positions(1), input.expanded_positions(2));
 
         Ok(())
     }
@@ -280,9 +344,7 @@ mod tests {
     #[test]
     fn test_pairs() -> Result<()> {
         let parsed_cosmic_map = input_generator(SAMPLE_INPUT)?;
-        let positions = parsed_cosmic_map.galaxy_positions();
-        let pairs = generate_pairs(positions);
-        assert_eq!(pairs.len(), 36);
+        assert_eq!(parsed_cosmic_map.pairs(2).count(), 36);
         Ok(())
     }
 
@@ -297,8 +359,74 @@ mod tests {
     #[test]
     fn part2_10_sample_input() -> Result<()> {
         let parsed_cosmic_map = input_generator(SAMPLE_INPUT)?;
-        let res = inner(parsed_cosmic_map, 10);
+        let res = parsed_cosmic_map.distance_sum(10)?;
         assert_eq!(res, 1030);
         Ok(())
     }
+
+    #[test]
+    fn part2_sample_input() -> Result<()> {
+        let parsed_cosmic_map = input_generator(SAMPLE_INPUT)?;
+        assert_eq!(part2(&parsed_cosmic_map)?, 82_000_210);
+        assert_eq!(parsed_cosmic_map.distance_sum(100)?, 8410);
+        Ok(())
+    }
+
+    #[test]
+    fn expansion_by_huge_factors() -> Result<()> {
+        let parsed_cosmic_map = input_generator(SAMPLE_INPUT)?;
+        let at = |expansion| parsed_cosmic_map.distance_sum(expansion);
+
+        // The sum grows linearly with the expansion, so extrapolate from two small factors
+        let per_step = at(3)? - at(2)?;
+        let expected = at(2)? + per_step * u128::from(u64::MAX - 2);
+
+        assert_eq!(expected, at(u64::MAX)?);
+        Ok(())
+    }
+
+    #[test]
+    fn distance_sum_matches_every_pair() -> Result<()> {
+        let parsed_cosmic_map = input_generator(SAMPLE_INPUT)?;
+
+        for expansion in [0, 1, 2, 10, 100, 1_000_000] {
+            let pairwise: u128 = parsed_cosmic_map
+                .pairs(expansion)
+                .map(|pair| pair.distance)
+                .sum();
+            assert_eq!(pairwise, parsed_cosmic_map.distance_sum(expansion)?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn nearest_and_farthest_pairs() -> Result<()> {
+        let parsed_cosmic_map = input_generator(SAMPLE_INPUT)?;
+        let mut all: Vec<_> = parsed_cosmic_map.pairs(2).collect();
+        all.sort_by_key(|pair| (pair.distance, pair.from.id, pair.to.id));
+
+        let nearest = parsed_cosmic_map.nearest_pairs(2, 3);
+        assert_eq!(all[..3], nearest);
+
+        let farthest = parsed_cosmic_map.farthest_pairs(2, 3);
+        assert_eq!(3, farthest.len());
+        assert!(farthest.windows(2).all(|w| w[0].distance >= w[1].distance));
+        assert_eq!(
+            all.last().map(|pair| pair.distance),
+            Some(farthest[0].distance)
+        );
+
+        // Galaxies 8 and 9 in the puzzle's numbering are 5 apart
+        let between = |pair: &GalaxyPair| (pair.from.id, pair.to.id) == (7, 8);
+        assert_eq!(
+            Some(5),
+            all.iter()
+                .find(|pair| between(pair))
+                .map(|pair| pair.distance)
+        );
+
+        assert_eq!(36, parsed_cosmic_map.nearest_pairs(2, 100).len());
+        assert!(parsed_cosmic_map.farthest_pairs(2, 0).is_empty());
+        Ok(())
+    }
 }
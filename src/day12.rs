@@ -1,15 +1,15 @@
 use aoc_runner_derive::{aoc, aoc_generator};
-use color_eyre::Result;
+use color_eyre::{eyre::eyre, Result};
 use itertools::Itertools;
 use once_cell::sync::Lazy;
-use std::fmt::{Display, Write};
+use std::collections::HashMap;
 
 static COLOR_EYRE: Lazy<()> = Lazy::new(|| color_eyre::install().unwrap());
 
 #[aoc_generator(day12)]
-pub fn input_generator(input: &str) -> Result<String> {
+pub fn input_generator(input: &str) -> Result<Vec<SpringRecord>> {
     Lazy::get(&COLOR_EYRE);
-    todo!()
+    parsers::parse_input(input).map_err(|e| eyre!(e.to_string()))
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -25,22 +25,109 @@ struct SpringGroup {
     count: usize,
 }
 
-type CheckSum = Vec<u64>;
+type CheckSum = Vec<usize>;
 
 #[derive(Debug, Clone)]
-struct SpringRecord {
+pub struct SpringRecord {
     groups: Vec<SpringGroup>,
     check_sum: CheckSum,
 }
 
+impl SpringRecord {
+    fn springs(&self) -> Vec<SpringState> {
+        self.groups
+            .iter()
+            .flat_map(|group| std::iter::repeat_n(group.state, group.count))
+            .collect()
+    }
+
+    /// Repeats the record `times` times, joining the copies with a single unknown spring
+    fn unfold(&self, times: usize) -> Self {
+        let joiner = SpringGroup {
+            state: SpringState::Unknown,
+            count: 1,
+        };
+
+        let copies = std::iter::repeat_n(self.groups.as_slice(), times);
+        let groups = Itertools::intersperse(copies, std::slice::from_ref(&joiner))
+            .flatten()
+            .copied()
+            .coalesce(|prev, next| {
+                if prev.state == next.state {
+                    Ok(SpringGroup {
+                        state: prev.state,
+                        count: prev.count + next.count,
+                    })
+                } else {
+                    Err((prev, next))
+                }
+            })
+            .collect();
+
+        Self {
+            groups,
+            check_sum: self.check_sum.repeat(times),
+        }
+    }
+
+    fn arrangements(&self) -> u64 {
+        let springs = self.springs();
+        let mut memo = HashMap::new();
+
+        count_arrangements(&springs, &self.check_sum, &mut memo)
+    }
+}
+
+/// Counts the ways the remaining `springs` can satisfy the remaining `check_sum`,
+/// memoized on how much of each is left
+fn count_arrangements(
+    springs: &[SpringState],
+    check_sum: &[usize],
+    memo: &mut HashMap<(usize, usize), u64>,
+) -> u64 {
+    let Some((&run, rest)) = check_sum.split_first() else {
+        return u64::from(!springs.contains(&SpringState::Damaged));
+    };
+
+    if springs.len() < run {
+        return 0;
+    }
+
+    let key = (springs.len(), check_sum.len());
+    if let Some(&count) = memo.get(&key) {
+        return count;
+    }
+
+    let mut count = 0;
+
+    if springs[0] != SpringState::Damaged {
+        count += count_arrangements(&springs[1..], check_sum, memo);
+    }
+
+    let fits_run = !springs[..run].contains(&SpringState::Operational)
+        && springs.get(run) != Some(&SpringState::Damaged);
+
+    if springs[0] != SpringState::Operational && fits_run {
+        let remaining = springs.get(run + 1..).unwrap_or_default();
+        count += count_arrangements(remaining, rest, memo);
+    }
+
+    memo.insert(key, count);
+
+    count
+}
+
 #[aoc(day12, part1)]
-pub fn part1(input: &str) -> Result<u64> {
-    todo!()
+pub fn part1(input: &[SpringRecord]) -> u64 {
+    input.iter().map(SpringRecord::arrangements).sum()
 }
 
 #[aoc(day12, part2)]
-pub fn part2(input: &str) -> Result<u64> {
-    todo!()
+pub fn part2(input: &[SpringRecord]) -> u64 {
+    input
+        .iter()
+        .map(|record| record.unfold(5).arrangements())
+        .sum()
 }
 
 mod parsers {
@@ -48,6 +135,7 @@ mod parsers {
     use itertools::Itertools;
     use nom::{
         character::complete::{line_ending, one_of, space1, u64},
+        combinator::map_res,
         multi::{many1, separated_list1},
         IResult, Parser,
     };
@@ -60,7 +148,9 @@ mod parsers {
 
     type Span<'a> = LocatedSpan<&'a str>;
 
-    pub(crate) fn parse_input(input: &str) -> color_eyre::Result<Vec<SpringRecord>, ParseError> {
+    pub(crate) fn parse_input(
+        input: &str,
+    ) -> color_eyre::Result<Vec<SpringRecord>, ParseError<'_>> {
         final_parser(spring_records)(Span::new(input))
     }
 
@@ -91,7 +181,7 @@ mod parsers {
     }
 
     fn check_sum(input: Span) -> IResult<Span, CheckSum, ParseError> {
-        separated_list1(tag(","), u64).parse(input)
+        separated_list1(tag(","), map_res(u64, usize::try_from)).parse(input)
     }
 
     fn spring_state(input: Span) -> IResult<Span, SpringState, ParseError> {
@@ -110,10 +200,11 @@ mod parsers {
 
 #[cfg(test)]
 mod tests {
-    use crate::day12::parsers::parse_input;
+    use crate::day12::{input_generator, parsers::parse_input, part1, part2};
     use color_eyre::eyre::Result;
     use indoc::indoc;
     use insta::assert_debug_snapshot;
+    use rstest::rstest;
     const SAMPLE_INPUT: &str = indoc! {
         "???.### 1,1,3
          .??..??...?##. 1,1,3
@@ -129,4 +220,38 @@ mod tests {
         assert_debug_snapshot!(actual);
         Ok(())
     }
+
+    #[rstest]
+    #[case("???.### 1,1,3", 1, 1)]
+    #[case(".??..??...?##. 1,1,3", 4, 16384)]
+    #[case("?#?#?#?#?#?#?#? 1,3,1,6", 1, 1)]
+    #[case("????.#...#... 4,1,1", 1, 16)]
+    #[case("????.######..#####. 1,6,5", 4, 2500)]
+    #[case("?###???????? 3,2,1", 10, 506_250)]
+    fn test_arrangements(
+        #[case] line: &str,
+        #[case] folded: u64,
+        #[case] unfolded: u64,
+    ) -> Result<()> {
+        let records = input_generator(line)?;
+        let record = records.first().expect("there is one record");
+
+        assert_eq!(folded, record.arrangements());
+        assert_eq!(unfolded, record.unfold(5).arrangements());
+        Ok(())
+    }
+
+    #[test]
+    fn part1_sample_input() -> Result<()> {
+        let records = input_generator(SAMPLE_INPUT)?;
+        assert_eq!(21, part1(&records));
+        Ok(())
+    }
+
+    #[test]
+    fn part2_sample_input() -> Result<()> {
+        let records = input_generator(SAMPLE_INPUT)?;
+        assert_eq!(525_152, part2(&records));
+        Ok(())
+    }
 }
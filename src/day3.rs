@@ -1,18 +1,19 @@
 use std::{collections::HashMap, default::Default, ops::Index};
 
 use aoc_runner_derive::{aoc, aoc_generator};
+use color_eyre::Result;
 use itertools::Itertools;
 
-use crate::utils::point::Point;
+use crate::utils::{grid::Grid, point::Point};
 
 #[aoc_generator(day3)]
-pub fn input_generator(input: &str) -> Schematic {
-    Schematic::from(input)
+pub fn input_generator(input: &str) -> Result<Schematic> {
+    Schematic::try_from(input)
 }
 
 #[derive(Debug, Clone)]
 pub struct Schematic {
-    grid: Vec<Vec<char>>,
+    grid: Grid<char>,
     cursor: Point,
 }
 
@@ -20,18 +21,20 @@ impl Index<&Point> for Schematic {
     type Output = char;
 
     fn index(&self, index: &Point) -> &Self::Output {
-        &self.grid[index.y as usize][index.x as usize]
+        &self.grid[*index]
     }
 }
 
-impl From<&str> for Schematic {
-    fn from(value: &str) -> Self {
-        let grid = value.lines().map(|line| line.chars().collect()).collect();
+impl TryFrom<&str> for Schematic {
+    type Error = color_eyre::Report;
 
-        Self {
+    fn try_from(value: &str) -> std::result::Result<Self, Self::Error> {
+        let grid = Grid::parse(value, Ok)?;
+
+        Ok(Self {
             grid,
             cursor: Point::default(),
-        }
+        })
     }
 }
 
@@ -44,10 +47,7 @@ pub fn part1(schematic: &Schematic) -> u64 {
 fn part_numbers(schematic: &Schematic) -> Vec<u64> {
     let mut schematic = schematic.clone();
     let mut valid_nums: Vec<u64> = Vec::new();
-    let max = Point {
-        x: schematic.grid.first().unwrap().len() as i64 - 1,
-        y: schematic.grid.len() as i64 - 1,
-    };
+    let max = schematic.grid.max_point().unwrap();
 
     let mut acc = String::new();
     let mut valid = false;
@@ -63,10 +63,9 @@ fn part_numbers(schematic: &Schematic) -> Vec<u64> {
             acc.push(schematic[&schematic.cursor]);
 
             let valid_neighbors: Vec<Point> = schematic
-                .cursor
-                .neighbors()
-                .into_iter()
-                .filter(|pt| pt.is_valid(&(0, 0).into(), &max))
+                .grid
+                .neighbors8(schematic.cursor)
+                .map(|(pt, _)| pt)
                 .collect();
 
             let is_part = valid_neighbors
@@ -101,10 +100,7 @@ fn part_numbers(schematic: &Schematic) -> Vec<u64> {
 fn potential_gears(schematic: &Schematic) -> Vec<(u64, Vec<Point>)> {
     let mut schematic = schematic.clone();
     let mut gear_nums: Vec<(u64, Vec<Point>)> = Vec::new();
-    let max = Point {
-        x: schematic.grid.first().unwrap().len() as i64 - 1,
-        y: schematic.grid.len() as i64 - 1,
-    };
+    let max = schematic.grid.max_point().unwrap();
 
     let mut acc = String::new();
     let mut valid = false;
@@ -129,10 +125,9 @@ fn potential_gears(schematic: &Schematic) -> Vec<(u64, Vec<Point>)> {
             acc.push(schematic[&schematic.cursor]);
 
             let valid_neighbors: Vec<Point> = schematic
-                .cursor
-                .neighbors()
-                .into_iter()
-                .filter(|pt| pt.is_valid(&(0, 0).into(), &max))
+                .grid
+                .neighbors8(schematic.cursor)
+                .map(|(pt, _)| pt)
                 .collect();
 
             let is_part = valid_neighbors
@@ -206,7 +201,7 @@ mod tests {
 ...$.*....
 .664.598.."#;
 
-        let input = input_generator(input);
+        let input = input_generator(input).unwrap();
         let expected_parts = vec![467, 35, 633, 617, 592, 755, 664, 598];
         let parts = part_numbers(&input);
         assert_eq!(expected_parts, parts);
@@ -230,7 +225,7 @@ mod tests {
 .*.........*
 1.1..503+.56"#;
 
-        let input = input_generator(input);
+        let input = input_generator(input).unwrap();
         let actual = part1(&input);
 
         assert_eq!(925, actual);
@@ -249,7 +244,7 @@ mod tests {
 ...$.*....
 .664.598.."#;
 
-        let input = input_generator(input);
+        let input = input_generator(input).unwrap();
         let actual = part2(&input);
         assert_eq!(467_835, actual);
     }
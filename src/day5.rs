@@ -1,45 +1,98 @@
-use std::{hash::Hash, marker::PhantomData};
+use std::{
+    cmp::min,
+    collections::HashSet,
+    fmt::{Display, Formatter},
+    ops::Range,
+};
 
 use aoc_runner_derive::{aoc, aoc_generator};
+use color_eyre::{
+    eyre::{ensure, eyre},
+    Result,
+};
 use itertools::Itertools;
-use rayon::prelude::*;
 
-macro_rules! create_id {
-    ($id_name:ident) => {
-        #[derive(Debug, Copy, Clone, PartialOrd, PartialEq, Eq, Hash)]
-        struct $id_name(u64);
+const SEED: &str = "seed";
+const LOCATION: &str = "location";
 
-        impl From<u64> for $id_name {
-            fn from(value: u64) -> Self {
-                Self(value)
-            }
+pub struct Almanac {
+    seeds: Vec<u64>,
+    maps: Vec<Map>,
+}
+
+impl Almanac {
+    fn new(seeds: Vec<u64>, maps: Vec<Map>) -> Result<Self> {
+        if let Some(duplicate) = maps.iter().map(|map| &map.src).duplicates().next() {
+            return Err(eyre!("more than one map converts from {duplicate}"));
         }
 
-        impl From<$id_name> for u64 {
-            fn from(value: $id_name) -> Self {
-                value.0
-            }
+        Ok(Self { seeds, maps })
+    }
+
+    /// The categories named in the almanac's map headers, in the order they first appear
+    #[must_use]
+    pub fn categories(&self) -> Vec<&str> {
+        self.maps
+            .iter()
+            .flat_map(|map| [map.src.as_str(), map.dest.as_str()])
+            .unique()
+            .collect()
+    }
+
+    /// Chains the maps leading from `from` to `to` into a single map.
+    ///
+    /// # Errors
+    ///
+    /// Fails when no map converts from one of the categories along the way, or when the
+    /// chain loops back on itself before reaching `to`.
+    pub fn conversion(&self, from: &str, to: &str) -> Result<Map> {
+        let mut conversion = Map::identity(from);
+        let mut seen = HashSet::from([from]);
+
+        while conversion.dest != to {
+            let next = self
+                .maps
+                .iter()
+                .find(|map| map.src == conversion.dest)
+                .ok_or_else(|| {
+                    eyre!(
+                        "no map converts from {} on the way from {from} to {to}",
+                        conversion.dest
+                    )
+                })?;
+
+            ensure!(
+                seen.insert(next.dest.as_str()),
+                "maps loop back to {} on the way from {from} to {to}",
+                next.dest
+            );
+
+            conversion = conversion.compose(next)?;
         }
-    };
+
+        Ok(conversion)
+    }
+
+    /// Looks up a single id of the `from` category as a `to` category id
+    ///
+    /// # Errors
+    ///
+    /// Fails when there is no chain of maps from `from` to `to`.
+    pub fn convert(&self, from: &str, to: &str, id: u64) -> Result<u64> {
+        Ok(self.conversion(from, to)?.get(id))
+    }
 }
 
-create_id!(Seed);
-create_id!(Soil);
-create_id!(Fertilizer);
-create_id!(Water);
-create_id!(Light);
-create_id!(Temperature);
-create_id!(Humidity);
-create_id!(Location);
-pub struct Almanac {
-    seeds: Vec<Seed>,
-    seed_to_soil: Map<Seed, Soil>,
-    soil_to_fertilizer: Map<Soil, Fertilizer>,
-    fertilizer_to_water: Map<Fertilizer, Water>,
-    water_to_light: Map<Water, Light>,
-    light_to_temperature: Map<Light, Temperature>,
-    temperature_to_humidity: Map<Temperature, Humidity>,
-    humidity_to_location: Map<Humidity, Location>,
+impl Display for Almanac {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "seeds: {}", self.seeds.iter().join(" "))?;
+
+        for map in &self.maps {
+            write!(f, "\n\n{map}")?;
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -50,215 +103,234 @@ struct MapLine {
 }
 
 impl MapLine {
+    fn src_end(&self) -> u64 {
+        self.src_start + self.count
+    }
+
     fn contains(&self, id: u64) -> bool {
-        (self.src_start..self.src_start + self.count).contains(&id)
+        (self.src_start..self.src_end()).contains(&id)
     }
 
     fn get(&self, id: u64) -> u64 {
-        let offset = self.dest_start as i64 - self.src_start as i64;
-
-        (id as i64 + offset) as u64
+        id - self.src_start + self.dest_start
     }
 }
 
-struct Map<S, D>
-where
-    D: From<u64>,
-    S: Into<u64>,
-{
-    _src: PhantomData<S>,
-    _dest: PhantomData<D>,
+#[derive(Debug, PartialEq)]
+pub struct Map {
+    src: String,
+    dest: String,
     lines: Vec<MapLine>,
 }
 
-impl<S, D> Map<S, D>
-where
-    D: From<u64> + Copy,
-    S: Into<u64> + Copy,
-{
-    fn get(&self, src: S) -> D {
-        self.lines
+impl Map {
+    #[must_use]
+    pub fn get(&self, id: u64) -> u64 {
+        let following = self.lines.partition_point(|line| line.src_start <= id);
+
+        following
+            .checked_sub(1)
+            .map(|idx| &self.lines[idx])
+            .filter(|line| line.contains(id))
+            .map_or(id, |line| line.get(id))
+    }
+
+    /// Splits `src` wherever it crosses the edge of a line, pairing each piece with the id its
+    /// start maps to. Ids not covered by any line map to themselves.
+    fn pieces(&self, src: Range<u64>) -> Vec<(Range<u64>, u64)> {
+        if src.is_empty() {
+            return Vec::new();
+        }
+
+        let mut pieces = Vec::new();
+        let mut cursor = src.start;
+
+        for line in self
+            .lines
             .iter()
-            .find(|line| line.contains(src.into()))
-            .map(|line| D::from(line.get(src.into())))
-            .unwrap_or(D::from(src.into()))
+            .filter(|line| line.src_end() > src.start && line.src_start < src.end)
+        {
+            if cursor < line.src_start {
+                pieces.push((cursor..line.src_start, cursor));
+                cursor = line.src_start;
+            }
+
+            let end = min(src.end, line.src_end());
+            pieces.push((cursor..end, line.get(cursor)));
+            cursor = end;
+        }
+
+        if cursor < src.end {
+            pieces.push((cursor..src.end, cursor));
+        }
+
+        pieces
     }
 
-    fn new(lines: Vec<MapLine>) -> Self {
+    /// Maps every id in `src` at once, splitting it wherever it crosses the edge of a line.
+    #[must_use]
+    pub fn get_range(&self, src: Range<u64>) -> Vec<Range<u64>> {
+        self.pieces(src)
+            .into_iter()
+            .map(|(piece, dest)| dest..dest + (piece.end - piece.start))
+            .collect()
+    }
+
+    #[must_use]
+    pub fn get_ranges(&self, srcs: &[Range<u64>]) -> Vec<Range<u64>> {
+        srcs.iter()
+            .flat_map(|src| self.get_range(src.clone()))
+            .collect()
+    }
+
+    /// Builds a single map equivalent to looking up in `self` and then in `next`
+    fn compose(&self, next: &Map) -> Result<Map> {
+        ensure!(
+            self.dest == next.src,
+            "cannot follow a map to {} with a map from {}",
+            self.dest,
+            next.src
+        );
+
+        let lines = self
+            .pieces(0..u64::MAX)
+            .into_iter()
+            .flat_map(|(piece, mid)| {
+                next.pieces(mid..mid + (piece.end - piece.start))
+                    .into_iter()
+                    .map(move |(next_piece, dest)| MapLine {
+                        src_start: piece.start + (next_piece.start - mid),
+                        dest_start: dest,
+                        count: next_piece.end - next_piece.start,
+                    })
+            })
+            .filter(|line| line.src_start != line.dest_start)
+            .coalesce(|prev, line| {
+                if prev.src_end() == line.src_start && prev.get(line.src_start) == line.dest_start {
+                    Ok(MapLine {
+                        count: prev.count + line.count,
+                        ..prev
+                    })
+                } else {
+                    Err((prev, line))
+                }
+            })
+            .collect();
+
+        Ok(Map::new(&self.src, &next.dest, lines))
+    }
+
+    fn identity(category: &str) -> Self {
+        Self::new(category, category, Vec::new())
+    }
+
+    fn new(src: &str, dest: &str, mut lines: Vec<MapLine>) -> Self {
+        lines.sort_by_key(|line| line.src_start);
+
         Self {
-            _src: Default::default(),
-            _dest: Default::default(),
+            src: src.to_owned(),
+            dest: dest.to_owned(),
             lines,
         }
     }
 }
 
+impl Display for Map {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-to-{} map:", self.src, self.dest)?;
+
+        for line in &self.lines {
+            write!(f, "\n{} {} {}", line.dest_start, line.src_start, line.count)?;
+        }
+
+        Ok(())
+    }
+}
+
 #[aoc_generator(day5)]
-pub fn input_generator(input: &str) -> color_eyre::Result<Almanac> {
+pub fn input_generator(input: &str) -> Result<Almanac> {
     parsers::parse_input(input)
 }
 
 #[aoc(day5, part1)]
-pub fn part1(almanac: &Almanac) -> u64 {
+pub fn part1(almanac: &Almanac) -> Result<u64> {
+    let seed_to_location = almanac.conversion(SEED, LOCATION)?;
+
     almanac
         .seeds
         .iter()
-        .map(|&seed| almanac.seed_to_soil.get(seed))
-        .map(|soil| almanac.soil_to_fertilizer.get(soil))
-        .map(|fertilizer| almanac.fertilizer_to_water.get(fertilizer))
-        .map(|water| almanac.water_to_light.get(water))
-        .map(|light| almanac.light_to_temperature.get(light))
-        .map(|temp| almanac.temperature_to_humidity.get(temp))
-        .map(|humidity| almanac.humidity_to_location.get(humidity))
-        .map(|loc| loc.0)
+        .map(|&seed| seed_to_location.get(seed))
         .min()
-        .unwrap()
+        .ok_or_else(|| eyre!("the almanac has no seeds"))
 }
 
 #[aoc(day5, part2)]
-pub fn part2(almanac: &Almanac) -> u64 {
-    let seeds: Vec<Seed> = almanac
+pub fn part2(almanac: &Almanac) -> Result<u64> {
+    let seeds: Vec<Range<u64>> = almanac
         .seeds
         .iter()
-        .chunks(2)
-        .into_iter()
-        .flat_map(|mut chunk| (chunk.next().unwrap().0..).take(chunk.next().unwrap().0 as usize))
-        .map(|seed| seed.into())
+        .tuples()
+        .map(|(&start, &count)| start..start + count)
+        .filter(|seeds| !seeds.is_empty())
         .collect();
 
-    seeds
-        .par_iter()
-        .map(|&seed| almanac.seed_to_soil.get(seed))
-        .map(|soil| almanac.soil_to_fertilizer.get(soil))
-        .map(|fertilizer| almanac.fertilizer_to_water.get(fertilizer))
-        .map(|water| almanac.water_to_light.get(water))
-        .map(|light| almanac.light_to_temperature.get(light))
-        .map(|temp| almanac.temperature_to_humidity.get(temp))
-        .map(|humidity| almanac.humidity_to_location.get(humidity))
-        .map(|loc| loc.0)
+    almanac
+        .conversion(SEED, LOCATION)?
+        .get_ranges(&seeds)
+        .iter()
+        .map(|loc| loc.start)
         .min()
-        .unwrap()
+        .ok_or_else(|| eyre!("the almanac has no seeds"))
 }
 
 mod parsers {
     use nom::{
         bytes::complete::tag,
         character,
-        character::complete::{newline, space0, space1},
-        combinator::{all_consuming, eof, map},
-        multi::separated_list1,
-        sequence::{preceded, terminated, tuple},
+        character::complete::{alpha1, newline, space0, space1},
+        combinator::{all_consuming, map},
+        multi::{count, separated_list1},
+        sequence::{preceded, separated_pair, terminated, tuple},
         Finish, IResult,
     };
 
-    use crate::day5::{
-        Almanac, Fertilizer, Humidity, Light, Location, Map, MapLine, Seed, Soil, Temperature,
-        Water,
-    };
+    use crate::day5::{Almanac, Map, MapLine};
 
     pub(crate) fn parse_input(input: &str) -> color_eyre::Result<Almanac> {
-        let (_, almanac) = all_consuming(almanac)(input)
+        let (_, (seeds, maps)) = all_consuming(almanac)(input)
             .map_err(|e| e.to_owned())
             .finish()?;
-        Ok(almanac)
+        Almanac::new(seeds, maps)
     }
 
-    fn almanac(input: &str) -> IResult<&str, Almanac> {
-        map(
-            tuple((
-                terminated(terminated(seeds, newline), newline),
-                terminated(terminated(seed_to_soil, newline), newline),
-                terminated(terminated(soil_to_fertilizer, newline), newline),
-                terminated(terminated(fertilizer_to_water, newline), newline),
-                terminated(terminated(water_to_light, newline), newline),
-                terminated(terminated(light_to_temperature, newline), newline),
-                terminated(terminated(temperature_to_humidity, newline), newline),
-                terminated(humidity_to_location, eof),
-            )),
-            |(
-                seeds,
-                seed_to_soil,
-                soil_to_fertilizer,
-                fertilizer_to_water,
-                water_to_light,
-                light_to_temperature,
-                temperature_to_humidity,
-                humidity_to_location,
-            )| {
-                Almanac {
-                    seeds,
-
-                    seed_to_soil,
-                    soil_to_fertilizer,
-                    fertilizer_to_water,
-                    water_to_light,
-                    light_to_temperature,
-                    temperature_to_humidity,
-                    humidity_to_location,
-                }
-            },
+    fn almanac(input: &str) -> IResult<&str, (Vec<u64>, Vec<Map>)> {
+        separated_pair(
+            seeds,
+            count(newline, 2),
+            separated_list1(count(newline, 2), category_map),
         )(input)
     }
 
-    fn seeds(input: &str) -> IResult<&str, Vec<Seed>> {
-        map(
-            preceded(
-                terminated(tag("seeds:"), space1),
-                separated_list1(space1, character::complete::u64),
-            ),
-            |ids| ids.iter().copied().map(Seed::from).collect(),
+    fn seeds(input: &str) -> IResult<&str, Vec<u64>> {
+        preceded(
+            terminated(tag("seeds:"), space1),
+            separated_list1(space1, character::complete::u64),
         )(input)
     }
 
-    macro_rules! map_parser {
-        ($parser_name:ident, $tag:expr, $source:ty, $dest:ty) => {
-            fn $parser_name(input: &str) -> IResult<&str, Map<$source, $dest>> {
-                let (input, lines) = preceded(
-                    terminated(
-                        terminated(tag($tag), space1),
-                        terminated(tag("map:"), newline),
-                    ),
-                    separated_list1(newline, map_line),
-                )(input)?;
-
-                Ok((input, Map::new(lines)))
-            }
-        };
+    fn category_map(input: &str) -> IResult<&str, Map> {
+        map(
+            separated_pair(header, newline, separated_list1(newline, map_line)),
+            |((src, dest), lines)| Map::new(src, dest, lines),
+        )(input)
     }
 
-    map_parser!(seed_to_soil, "seed-to-soil", Seed, Soil);
-
-    map_parser!(soil_to_fertilizer, "soil-to-fertilizer", Soil, Fertilizer);
-
-    map_parser!(
-        fertilizer_to_water,
-        "fertilizer-to-water",
-        Fertilizer,
-        Water
-    );
-
-    map_parser!(water_to_light, "water-to-light", Water, Light);
-
-    map_parser!(
-        light_to_temperature,
-        "light-to-temperature",
-        Light,
-        Temperature
-    );
-
-    map_parser!(
-        temperature_to_humidity,
-        "temperature-to-humidity",
-        Temperature,
-        Humidity
-    );
-
-    map_parser!(
-        humidity_to_location,
-        "humidity-to-location",
-        Humidity,
-        Location
-    );
+    fn header(input: &str) -> IResult<&str, (&str, &str)> {
+        terminated(
+            separated_pair(alpha1, tag("-to-"), alpha1),
+            preceded(space1, tag("map:")),
+        )(input)
+    }
 
     fn map_line(input: &str) -> IResult<&str, MapLine> {
         map(
@@ -290,6 +362,12 @@ mod parsers {
             };
             assert_eq!(expected, actual);
         }
+
+        #[test]
+        fn parse_header() {
+            let (_, actual) = header("water-to-light map:").unwrap();
+            assert_eq!(("water", "light"), actual);
+        }
     }
 }
 
@@ -331,16 +409,179 @@ humidity-to-location map:
 60 56 37
 56 93 4";
 
+    const CHAIN: [&str; 8] = [
+        "seed",
+        "soil",
+        "fertilizer",
+        "water",
+        "light",
+        "temperature",
+        "humidity",
+        "location",
+    ];
+
     #[test]
     fn parse_input() {
         let _ = input_generator(INPUT).unwrap();
     }
 
+    #[test]
+    fn categories_follow_headers() {
+        let almanac = input_generator(INPUT).unwrap();
+
+        assert_eq!(CHAIN.to_vec(), almanac.categories());
+    }
+
+    #[test]
+    fn parse_reordered_sections() {
+        let (seeds, sections) = INPUT.split_once("\n\n").unwrap();
+        let reordered = format!(
+            "{seeds}\n\n{}",
+            sections
+                .split("\n\n")
+                .collect::<Vec<_>>()
+                .iter()
+                .rev()
+                .join("\n\n")
+        );
+
+        let almanac = input_generator(&reordered).unwrap();
+
+        assert_eq!(35, part1(&almanac).unwrap());
+    }
+
+    #[test]
+    fn missing_section_is_an_error() {
+        let broken = INPUT.replace("water-to-light", "water-to-lamp");
+
+        let almanac = input_generator(&broken).unwrap();
+        let err = part1(&almanac).err().unwrap();
+
+        assert_eq!(
+            "no map converts from lamp on the way from seed to location",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn parse_almanac_ending_elsewhere() {
+        let (shorter, _) = INPUT.split_once("\n\nhumidity-to-location").unwrap();
+
+        let almanac = input_generator(shorter).unwrap();
+
+        assert_eq!(CHAIN[..7].to_vec(), almanac.categories());
+        assert_eq!(78, almanac.convert("seed", "humidity", 79).unwrap());
+        assert!(part1(&almanac).is_err());
+    }
+
+    #[test]
+    fn duplicate_source_is_an_error() {
+        let broken = INPUT.replace("light-to-temperature", "water-to-temperature");
+
+        let err = input_generator(&broken).err().unwrap();
+
+        assert_eq!("more than one map converts from water", err.to_string());
+    }
+
+    #[test]
+    fn conversion_between_any_categories() {
+        let almanac = input_generator(INPUT).unwrap();
+
+        assert_eq!(81, almanac.convert("seed", "soil", 79).unwrap());
+        assert_eq!(74, almanac.convert("soil", "light", 81).unwrap());
+        assert_eq!(82, almanac.convert("temperature", "location", 78).unwrap());
+        assert!(almanac.convert("location", "seed", 82).is_err());
+    }
+
+    #[test]
+    fn range_mapping_splits_on_line_edges() {
+        let almanac = input_generator(INPUT).unwrap();
+        let seed_to_soil = almanac.conversion("seed", "soil").unwrap();
+
+        let actual = seed_to_soil.get_range(45..100);
+
+        assert_eq!(vec![45..50, 52..100, 50..52], actual);
+        assert!(seed_to_soil.get_range(60..60).is_empty());
+    }
+
+    #[test]
+    fn range_mapping_matches_single_lookups() {
+        let almanac = input_generator(INPUT).unwrap();
+
+        for map in &almanac.maps {
+            let expected: Vec<u64> = (0..100).map(|id| map.get(id)).collect();
+            let actual: Vec<u64> = map.get_range(0..100).into_iter().flatten().collect();
+
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn composed_map_matches_chained_lookups() {
+        let almanac = input_generator(INPUT).unwrap();
+
+        let seed_to_location = almanac.conversion(SEED, LOCATION).unwrap();
+
+        for id in 0..120 {
+            let location = CHAIN
+                .iter()
+                .tuple_windows()
+                .fold(id, |id, (from, to)| almanac.convert(from, to, id).unwrap());
+
+            assert_eq!(location, seed_to_location.get(id));
+        }
+    }
+
+    #[test]
+    fn composed_map_merges_into_single_table() {
+        let first = Map::new(
+            "seed",
+            "soil",
+            vec![
+                MapLine {
+                    src_start: 0,
+                    dest_start: 10,
+                    count: 10,
+                },
+                MapLine {
+                    src_start: 10,
+                    dest_start: 20,
+                    count: 5,
+                },
+            ],
+        );
+        let second = Map::new(
+            "soil",
+            "fertilizer",
+            vec![MapLine {
+                src_start: 12,
+                dest_start: 2,
+                count: 6,
+            }],
+        );
+
+        let composed = first.compose(&second).unwrap();
+
+        assert_eq!(
+            "seed-to-fertilizer map:\n10 0 2\n18 8 7\n5 15 3",
+            composed.to_string()
+        );
+        assert!(second.compose(&first).is_err());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let almanac = input_generator(INPUT).unwrap();
+        let reparsed = input_generator(&almanac.to_string()).unwrap();
+
+        assert_eq!(almanac.to_string(), reparsed.to_string());
+    }
+
     #[test]
     fn part1_sample_input() {
         let almanac = input_generator(INPUT).unwrap();
 
-        let actual = part1(&almanac);
+        let actual = part1(&almanac).unwrap();
 
         assert_eq!(35, actual);
     }
@@ -349,8 +590,23 @@ humidity-to-location map:
     fn part2_sample_input() {
         let almanac = input_generator(INPUT).unwrap();
 
-        let actual = part2(&almanac);
+        let actual = part2(&almanac).unwrap();
+
+        assert_eq!(46, actual);
+    }
+
+    #[test]
+    fn part2_skips_empty_seed_ranges() {
+        let almanac = input_generator(&INPUT.replace("79 14 55 13", "79 14 27 0")).unwrap();
+        let seed_to_location = almanac.conversion(SEED, LOCATION).unwrap();
+        let expected = (79..93)
+            .map(|seed| seed_to_location.get(seed))
+            .min()
+            .unwrap();
+
+        let actual = part2(&almanac).unwrap();
 
+        assert_eq!(expected, actual);
         assert_eq!(46, actual);
     }
 }
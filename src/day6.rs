@@ -1,22 +1,49 @@
 use aoc_runner_derive::{aoc, aoc_generator};
-use color_eyre::Result;
+use color_eyre::{eyre::OptionExt, Result};
 
 #[derive(Debug, Copy, Clone, PartialOrd, PartialEq)]
 pub struct Race {
-    time: u64,
-    distance: u64,
+    time: u128,
+    distance: u128,
 }
 
 impl Race {
-    fn chances(&self) -> usize {
-        (0..=self.time)
-            .map(|hold| {
-                let run = self.time - hold;
-
-                hold * run
-            })
-            .filter(|&distance| distance > self.distance)
-            .count()
+    fn beats_record(&self, hold: u128) -> bool {
+        hold * (self.time - hold) > self.distance
+    }
+
+    /// Counts the hold times that beat the record by solving `hold * (time - hold) > distance`.
+    /// The winning holds are symmetric around `time / 2`, so only the smallest one is searched
+    /// for, starting from the integer square root estimate of the lower root.
+    fn chances(&self) -> Result<u128> {
+        let squared = self
+            .time
+            .checked_mul(self.time)
+            .ok_or_eyre("race time is too large to square")?;
+        let record = self
+            .distance
+            .checked_mul(4)
+            .ok_or_eyre("race distance is too large")?;
+
+        let Some(discriminant) = squared.checked_sub(record) else {
+            return Ok(0);
+        };
+
+        let half = self.time / 2;
+        let mut lowest = (self.time - discriminant.isqrt()) / 2;
+
+        while lowest > 0 && self.beats_record(lowest - 1) {
+            lowest -= 1;
+        }
+        while lowest <= half && !self.beats_record(lowest) {
+            lowest += 1;
+        }
+
+        if lowest > half {
+            Ok(0)
+        } else {
+            Ok(self.time - 2 * lowest + 1)
+        }
     }
 }
 
@@ -26,12 +53,16 @@ pub fn input_generator(input: &str) -> Result<Vec<Race>> {
 }
 
 #[aoc(day6, part1)]
-pub fn part1(races: &[Race]) -> usize {
-    races.iter().map(Race::chances).product()
+pub fn part1(races: &[Race]) -> Result<u128> {
+    races.iter().try_fold(1u128, |product, race| {
+        product
+            .checked_mul(race.chances()?)
+            .ok_or_eyre("the product of the chances overflows")
+    })
 }
 
 #[aoc(day6, part2)]
-pub fn part2(races: &[Race]) -> Result<usize> {
+pub fn part2(races: &[Race]) -> Result<u128> {
     let (time, dist) = races.iter().fold(
         (String::new(), String::new()),
         |(mut time, mut dist), race| {
@@ -47,7 +78,7 @@ pub fn part2(races: &[Race]) -> Result<usize> {
         distance: dist.parse()?,
     };
 
-    Ok(race.chances())
+    race.chances()
 }
 
 mod parsers {
@@ -84,17 +115,17 @@ mod parsers {
         )(input)
     }
 
-    fn time_line(input: &str) -> IResult<&str, Vec<u64>> {
+    fn time_line(input: &str) -> IResult<&str, Vec<u128>> {
         preceded(
             terminated(tag("Time:"), space1),
-            separated_list1(space1, character::complete::u64),
+            separated_list1(space1, character::complete::u128),
         )(input)
     }
 
-    fn distance_line(input: &str) -> IResult<&str, Vec<u64>> {
+    fn distance_line(input: &str) -> IResult<&str, Vec<u128>> {
         preceded(
             terminated(tag("Distance:"), space1),
-            separated_list1(space1, character::complete::u64),
+            separated_list1(space1, character::complete::u128),
         )(input)
     }
 }
@@ -138,7 +169,7 @@ Distance:  9  40  200";
     fn part_1() -> Result<()> {
         let input = parse_input(SAMPLE_INPUT)?;
 
-        let actual = part1(&input);
+        let actual = part1(&input)?;
 
         assert_eq!(288, actual);
 
@@ -155,4 +186,76 @@ Distance:  9  40  200";
 
         Ok(())
     }
+
+    fn brute_force_chances(race: &Race) -> u128 {
+        (0..=race.time)
+            .filter(|&hold| hold * (race.time - hold) > race.distance)
+            .map(|_| 1)
+            .sum()
+    }
+
+    #[test]
+    fn chances_match_brute_force_for_small_races() -> Result<()> {
+        for time in 0..60 {
+            for distance in 0..=time * time / 4 + 1 {
+                let race = Race { time, distance };
+                assert_eq!(brute_force_chances(&race), race.chances()?, "{race:?}");
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn chances_match_brute_force_for_random_races() -> Result<()> {
+        // xorshift keeps the races reproducible without pulling in a rng crate
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            u128::from(state)
+        };
+
+        for _ in 0..200 {
+            let time = next() % 100_000;
+            let distance = next() % (time * time / 4 + 10);
+            let race = Race { time, distance };
+
+            assert_eq!(brute_force_chances(&race), race.chances()?, "{race:?}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn chances_for_huge_races() -> Result<()> {
+        let race = Race {
+            time: 1 << 62,
+            distance: 1 << 100,
+        };
+
+        assert_eq!(4_611_685_468_671_541_247, race.chances()?);
+        assert!(Race {
+            time: u128::MAX,
+            distance: 0
+        }
+        .chances()
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn part1_product_overflow_is_an_error() {
+        let races = vec![
+            Race {
+                time: 1 << 62,
+                distance: 0,
+            };
+            3
+        ];
+
+        assert!(part1(&races).is_err());
+    }
 }
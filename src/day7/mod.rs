@@ -1,2 +1,605 @@
-mod part1;
-mod part2;
+use std::{
+    cmp::{Ordering, Reverse},
+    fmt::{Debug, Display, Formatter},
+    marker::PhantomData,
+};
+
+use color_eyre::{eyre::eyre, Result};
+use itertools::Itertools;
+use nom::{combinator::all_consuming, Finish};
+
+pub mod part1;
+pub mod part2;
+
+pub use part1::Standard;
+pub use part2::Jokers;
+
+#[derive(Debug, Copy, Clone, PartialOrd, PartialEq, Eq, Hash, Ord)]
+pub enum Card {
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+    Ace,
+}
+
+impl Card {
+    pub const ALL: [Card; 13] = [
+        Card::Two,
+        Card::Three,
+        Card::Four,
+        Card::Five,
+        Card::Six,
+        Card::Seven,
+        Card::Eight,
+        Card::Nine,
+        Card::Ten,
+        Card::Jack,
+        Card::Queen,
+        Card::King,
+        Card::Ace,
+    ];
+}
+
+impl TryFrom<char> for Card {
+    type Error = color_eyre::Report;
+
+    fn try_from(value: char) -> std::result::Result<Self, Self::Error> {
+        match value {
+            '2' => Ok(Card::Two),
+            '3' => Ok(Card::Three),
+            '4' => Ok(Card::Four),
+            '5' => Ok(Card::Five),
+            '6' => Ok(Card::Six),
+            '7' => Ok(Card::Seven),
+            '8' => Ok(Card::Eight),
+            '9' => Ok(Card::Nine),
+            'T' => Ok(Card::Ten),
+            'J' => Ok(Card::Jack),
+            'Q' => Ok(Card::Queen),
+            'K' => Ok(Card::King),
+            'A' => Ok(Card::Ace),
+            _ => Err(eyre!("unrecognized card type")),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialOrd, PartialEq, Ord, Eq)]
+pub enum HandType {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeKind,
+    FullHouse,
+    FourKind,
+    FiveKind,
+}
+
+/// How a game of Camel Cards is scored. Every method has a default, so a variant only
+/// needs to say what makes it different.
+pub trait Rules {
+    /// Whether `card` stands in for whichever card makes the strongest hand
+    #[must_use]
+    fn is_wild(_card: Card) -> bool {
+        false
+    }
+
+    /// How strong `card` is when breaking ties, where wild cards are the weakest
+    #[must_use]
+    fn card_strength(card: Card) -> u8 {
+        if Self::is_wild(card) {
+            0
+        } else {
+            card as u8 + 1
+        }
+    }
+
+    /// The position of the card that decides between two hands of the same type, by
+    /// default the first one where they differ in strength. `None` when they tie.
+    #[must_use]
+    fn deciding_card(cards: &[Card], other: &[Card]) -> Option<usize> {
+        cards
+            .iter()
+            .zip(other)
+            .position(|(&card, &other)| Self::card_strength(card) != Self::card_strength(other))
+    }
+}
+
+pub struct Hand<R> {
+    cards: Vec<Card>,
+    bid: u64,
+    rules: PhantomData<fn() -> R>,
+}
+
+impl<R> Hand<R> {
+    fn new(cards: Vec<Card>, bid: u64) -> Self {
+        Self {
+            cards,
+            bid,
+            rules: PhantomData,
+        }
+    }
+
+    #[must_use]
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    #[must_use]
+    pub fn bid(&self) -> u64 {
+        self.bid
+    }
+}
+
+impl<R: Rules> Hand<R> {
+    #[must_use]
+    pub fn hand_type(&self) -> HandType {
+        self.explain().hand_type
+    }
+
+    /// Classifies the hand, with every wild card joining the largest group of the others,
+    /// or the strongest of those when groups are the same size
+    #[must_use]
+    pub fn explain(&self) -> Explanation {
+        let mut groups: Vec<(Card, usize)> = self
+            .cards
+            .iter()
+            .filter(|&&card| !R::is_wild(card))
+            .counts()
+            .into_iter()
+            .map(|(&card, count)| (card, count))
+            .sorted_unstable_by_key(|&(card, count)| Reverse((count, R::card_strength(card))))
+            .collect();
+
+        let wild: Vec<usize> = self
+            .cards
+            .iter()
+            .positions(|&card| R::is_wild(card))
+            .collect();
+        if !wild.is_empty() {
+            if groups.is_empty() {
+                let strongest = Card::ALL
+                    .into_iter()
+                    .filter(|&card| !R::is_wild(card))
+                    .max_by_key(|&card| R::card_strength(card))
+                    .unwrap_or(Card::Ace);
+                groups.push((strongest, 0));
+            }
+            groups[0].1 += wild.len();
+        }
+
+        let hand_type = match groups.iter().map(|&(_, count)| count).collect_vec()[..] {
+            [5, ..] => HandType::FiveKind,
+            [4, ..] => HandType::FourKind,
+            [3, 2, ..] => HandType::FullHouse,
+            [3, ..] => HandType::ThreeKind,
+            [2, 2, ..] => HandType::TwoPair,
+            [2, ..] => HandType::OnePair,
+            _ => HandType::HighCard,
+        };
+
+        let wildcards = wild
+            .into_iter()
+            .map(|position| WildCard {
+                position,
+                card: self.cards[position],
+                played_as: groups[0].0,
+            })
+            .collect();
+
+        Explanation {
+            hand_type,
+            groups,
+            wildcards,
+        }
+    }
+
+    /// Compares this hand with `other`, saying what decided it
+    #[must_use]
+    pub fn explain_cmp(&self, other: &Self) -> Comparison {
+        let (ours, theirs) = (self.hand_type(), other.hand_type());
+        if ours != theirs {
+            return Comparison {
+                ordering: ours.cmp(&theirs),
+                decider: Decider::HandType { ours, theirs },
+            };
+        }
+
+        match R::deciding_card(&self.cards, &other.cards) {
+            Some(position) => {
+                let (ours, theirs) = (self.cards[position], other.cards[position]);
+                Comparison {
+                    ordering: R::card_strength(ours).cmp(&R::card_strength(theirs)),
+                    decider: Decider::Card {
+                        position,
+                        ours,
+                        theirs,
+                    },
+                }
+            }
+            None => Comparison {
+                ordering: Ordering::Equal,
+                decider: Decider::Tie,
+            },
+        }
+    }
+}
+
+/// Why a hand was classified the way it was
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub hand_type: HandType,
+    /// Each card in the hand and how many of it there are once wild cards are played,
+    /// largest group first
+    pub groups: Vec<(Card, usize)>,
+    /// What each wild card was played as
+    pub wildcards: Vec<WildCard>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WildCard {
+    /// Where the card is in the hand
+    pub position: usize,
+    pub card: Card,
+    pub played_as: Card,
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let groups = self
+            .groups
+            .iter()
+            .map(|(card, count)| format!("{count} x {card:?}"))
+            .join(", ");
+        write!(f, "{:?} from {groups}", self.hand_type)?;
+
+        for wild in &self.wildcards {
+            write!(
+                f,
+                "; {:?} at {} played as {:?}",
+                wild.card, wild.position, wild.played_as
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// How two hands compare, and what decided it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Comparison {
+    pub ordering: Ordering,
+    pub decider: Decider,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decider {
+    /// The hands are different types
+    HandType { ours: HandType, theirs: HandType },
+    /// The hands are the same type, and the cards at `position` are the first to differ
+    Card {
+        position: usize,
+        ours: Card,
+        theirs: Card,
+    },
+    /// Nothing tells the hands apart
+    Tie,
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.decider {
+            Decider::HandType { ours, theirs } => {
+                write!(f, "{:?}: {ours:?} against {theirs:?}", self.ordering)
+            }
+            Decider::Card {
+                position,
+                ours,
+                theirs,
+            } => write!(
+                f,
+                "{:?}: same type, {ours:?} against {theirs:?} at card {position}",
+                self.ordering
+            ),
+            Decider::Tie => write!(f, "{:?}: same type and cards", self.ordering),
+        }
+    }
+}
+
+impl<R> Clone for Hand<R> {
+    fn clone(&self) -> Self {
+        Self::new(self.cards.clone(), self.bid)
+    }
+}
+
+impl<R> Debug for Hand<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Hand")
+            .field("cards", &self.cards)
+            .field("bid", &self.bid)
+            .finish()
+    }
+}
+
+impl<R> TryFrom<&str> for Hand<R> {
+    type Error = color_eyre::Report;
+
+    fn try_from(value: &str) -> std::result::Result<Self, Self::Error> {
+        let (_, hand) = all_consuming(parsers::hand)(value)
+            .map_err(|e| e.to_owned())
+            .finish()?;
+        Ok(hand)
+    }
+}
+
+impl<R: Rules> PartialOrd for Hand<R> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<R: Rules> PartialEq for Hand<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl<R: Rules> Eq for Hand<R> {}
+
+impl<R: Rules> Ord for Hand<R> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.explain_cmp(other).ordering
+    }
+}
+
+/// Every hand alongside its rank, from the weakest at rank 1 to the strongest
+#[must_use]
+pub fn ranking<R: Rules>(hands: &[Hand<R>]) -> Vec<(u64, &Hand<R>)> {
+    hands
+        .iter()
+        .sorted()
+        .zip(1..)
+        .map(|(hand, rank)| (rank, hand))
+        .collect()
+}
+
+/// What every hand wins, its bid times its rank, added up.
+///
+/// # Errors
+///
+/// Fails when the total does not fit in a `u64`.
+pub fn total_winnings<R: Rules>(hands: &[Hand<R>]) -> Result<u64> {
+    ranking(hands)
+        .into_iter()
+        .map(|(rank, hand)| hand.bid.checked_mul(rank))
+        .try_fold(0u64, |total, winnings| total.checked_add(winnings?))
+        .ok_or_else(|| eyre!("total winnings overflow a u64"))
+}
+
+mod parsers {
+    use nom::{
+        character,
+        character::complete::{anychar, newline, space1},
+        combinator::{all_consuming, map, map_res},
+        multi::{many1, separated_list1},
+        sequence::separated_pair,
+        Finish, IResult,
+    };
+
+    use crate::day7::{Card, Hand};
+
+    pub(crate) fn parse_input<R>(input: &str) -> color_eyre::Result<Vec<Hand<R>>> {
+        let (_, hands) = all_consuming(hands)(input)
+            .map_err(|e| e.to_owned())
+            .finish()?;
+        Ok(hands)
+    }
+
+    fn hands<R>(input: &str) -> IResult<&str, Vec<Hand<R>>> {
+        separated_list1(newline, hand)(input)
+    }
+
+    pub(crate) fn hand<R>(input: &str) -> IResult<&str, Hand<R>> {
+        map(
+            separated_pair(cards, space1, character::complete::u64),
+            |(cards, bid)| Hand::new(cards, bid),
+        )(input)
+    }
+
+    fn cards(input: &str) -> IResult<&str, Vec<Card>> {
+        many1(card)(input)
+    }
+
+    fn card(input: &str) -> IResult<&str, Card> {
+        map_res(anychar, Card::try_from)(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner;
+    use color_eyre::Result;
+    use rstest::rstest;
+
+    const SAMPLE_INPUT: &str = "32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483";
+
+    #[rstest]
+    #[case(1, "6440")]
+    #[case(2, "5905")]
+    fn test_registered_parts(#[case] part: u32, #[case] expected: &str) -> Result<()> {
+        let answer = runner::find(7, part)
+            .expect("both day 7 parts are registered")
+            .run(SAMPLE_INPUT)?;
+
+        assert_eq!(expected, answer.value);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ranking() -> Result<()> {
+        let hands = parsers::parse_input::<Jokers>(SAMPLE_INPUT)?;
+
+        let ranked: Vec<_> = ranking(&hands)
+            .into_iter()
+            .map(|(rank, hand)| (rank, hand.bid()))
+            .collect();
+
+        assert_eq!(
+            vec![(1, 765), (2, 28), (3, 684), (4, 483), (5, 220)],
+            ranked
+        );
+        assert_eq!(5905, total_winnings(&hands)?);
+        Ok(())
+    }
+
+    /// Jacks and twos are both wild
+    struct DoubleWild;
+
+    impl Rules for DoubleWild {
+        fn is_wild(card: Card) -> bool {
+            matches!(card, Card::Jack | Card::Two)
+        }
+    }
+
+    #[rstest]
+    #[case("23456 0", HandType::OnePair)]
+    #[case("2J456 0", HandType::ThreeKind)]
+    #[case("2JJ2A 0", HandType::FiveKind)]
+    #[case("33442 0", HandType::FullHouse)]
+    #[case("3456A 0", HandType::HighCard)]
+    fn test_multiple_wildcards(#[case] hand: &str, #[case] hand_type: HandType) -> Result<()> {
+        let hand: Hand<DoubleWild> = hand.try_into()?;
+        assert_eq!(hand_type, hand.hand_type());
+        Ok(())
+    }
+
+    #[test]
+    fn test_wildcards_are_weakest() -> Result<()> {
+        let two: Hand<DoubleWild> = "2AAAA 0".try_into()?;
+        let jack: Hand<DoubleWild> = "JAAAA 0".try_into()?;
+        let aces: Hand<DoubleWild> = "AAAAA 0".try_into()?;
+
+        assert!(two == jack);
+        assert!(jack < aces);
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain_jokers() -> Result<()> {
+        let hand: Hand<Jokers> = "KTJJT 220".try_into()?;
+        let explanation = hand.explain();
+
+        assert_eq!(HandType::FourKind, explanation.hand_type);
+        assert_eq!(vec![(Card::Ten, 4), (Card::King, 1)], explanation.groups);
+        assert_eq!(
+            vec![
+                WildCard {
+                    position: 2,
+                    card: Card::Jack,
+                    played_as: Card::Ten
+                },
+                WildCard {
+                    position: 3,
+                    card: Card::Jack,
+                    played_as: Card::Ten
+                },
+            ],
+            explanation.wildcards
+        );
+        assert_eq!(
+            "FourKind from 4 x Ten, 1 x King; Jack at 2 played as Ten; Jack at 3 played as Ten",
+            explanation.to_string()
+        );
+        Ok(())
+    }
+
+    fn explain<R: Rules>(hand: &str) -> Result<Explanation> {
+        Ok(Hand::<R>::try_from(hand)?.explain())
+    }
+
+    #[rstest]
+    #[case::standard(explain::<Standard>, "KKQQJ 0", HandType::TwoPair, Card::King, 0)]
+    #[case::strongest_group(explain::<Jokers>, "KKQQJ 0", HandType::FullHouse, Card::King, 1)]
+    #[case::all_wild(explain::<Jokers>, "JJJJJ 0", HandType::FiveKind, Card::Ace, 5)]
+    #[case::no_jokers(explain::<Jokers>, "KKQQ2 0", HandType::TwoPair, Card::King, 0)]
+    fn test_explain_wild_assignment(
+        #[case] explain: fn(&str) -> Result<Explanation>,
+        #[case] hand: &str,
+        #[case] hand_type: HandType,
+        #[case] top: Card,
+        #[case] wild: usize,
+    ) -> Result<()> {
+        let explanation = explain(hand)?;
+
+        assert_eq!(hand_type, explanation.hand_type);
+        assert_eq!(Some(top), explanation.groups.first().map(|&(card, _)| card));
+        assert_eq!(wild, explanation.wildcards.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain_cmp() -> Result<()> {
+        let one_pair: Hand<Standard> = "32T3K 765".try_into()?;
+        let kings: Hand<Standard> = "KK677 28".try_into()?;
+        let tens: Hand<Standard> = "KTJJT 220".try_into()?;
+
+        let by_type = one_pair.explain_cmp(&kings);
+        assert_eq!(
+            Decider::HandType {
+                ours: HandType::OnePair,
+                theirs: HandType::TwoPair
+            },
+            by_type.decider
+        );
+        assert_eq!(Ordering::Less, by_type.ordering);
+
+        let by_card = kings.explain_cmp(&tens);
+        assert_eq!(
+            Decider::Card {
+                position: 1,
+                ours: Card::King,
+                theirs: Card::Ten
+            },
+            by_card.decider
+        );
+        assert_eq!(
+            "Greater: same type, King against Ten at card 1",
+            by_card.to_string()
+        );
+
+        assert_eq!(Decider::Tie, kings.explain_cmp(&kings).decider);
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain_cmp_with_jokers() -> Result<()> {
+        let weaker: Hand<Jokers> = "JKKK2 0".try_into()?;
+        let stronger: Hand<Jokers> = "QQQQ2 0".try_into()?;
+
+        let comparison = weaker.explain_cmp(&stronger);
+
+        assert_eq!(Ordering::Less, comparison.ordering);
+        assert_eq!(
+            Decider::Card {
+                position: 0,
+                ours: Card::Jack,
+                theirs: Card::Queen
+            },
+            comparison.decider
+        );
+        Ok(())
+    }
+}
@@ -1,8 +1,15 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+};
 
 use aoc_runner_derive::{aoc, aoc_generator};
-use color_eyre::{eyre::eyre, Result};
-use num::integer::lcm;
+use color_eyre::{
+    eyre::{bail, eyre},
+    Result,
+};
+use itertools::Itertools;
+use num::Integer;
 
 #[aoc_generator(day8)]
 pub fn input_generator(input: &str) -> Result<Map> {
@@ -10,73 +17,413 @@ pub fn input_generator(input: &str) -> Result<Map> {
 }
 
 #[aoc(day8, part1)]
-pub fn part1(map: &Map) -> Result<usize> {
-    let mut steps = map.steps.iter().cycle();
-    let mut current = map.nodes.get("AAA").unwrap();
-    let mut count = 0;
+pub fn part1(map: &Map) -> Result<u64> {
+    Ok(map.steps_between("AAA", "ZZZ")?)
+}
+
+#[aoc(day8, part2)]
+pub fn part2(map: &Map) -> Result<u64> {
+    Ok(map.steps_together(is_ghost_start, is_ghost_goal)?)
+}
 
-    while current.id != "ZZZ" {
-        let step = steps.next().unwrap();
+fn is_ghost_start(name: &str) -> bool {
+    name.ends_with('A')
+}
+
+fn is_ghost_goal(name: &str) -> bool {
+    name.ends_with('Z')
+}
 
-        current = match step {
-            Step::Left => map.nodes.get(&current.left).unwrap(),
-            Step::Right => map.nodes.get(&current.right).unwrap(),
+/// Why a walk through the network has no answer
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum TraversalError {
+    /// A start or goal was named that isn't in the map
+    MissingNode(String),
+    /// No node is a start
+    NoStart,
+    /// Walking from these starts came back to a state it had already been in without
+    /// ever having all of them on a goal at once, so it never will
+    Unreachable { starts: Vec<String> },
+    /// The step count is too large to work out
+    Overflow,
+}
+
+impl Display for TraversalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingNode(name) => write!(f, "{name} is not in the map"),
+            Self::NoStart => write!(f, "no node is a start"),
+            Self::Unreachable { starts } => match starts.as_slice() {
+                [start] => write!(f, "no goal can be reached from {start}"),
+                _ => write!(
+                    f,
+                    "the walks from {} are never all on a goal at the same time",
+                    starts.join(", ")
+                ),
+            },
+            Self::Overflow => write!(f, "the step count overflows"),
+        }
+    }
+}
+
+impl std::error::Error for TraversalError {}
+
+/// How a walk from one start settles into a loop. After `start` steps the walk is back in
+/// a state it has been in before, that is on the same node about to take the same step,
+/// so from there on it repeats every `length` steps.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Cycle {
+    pub start: u64,
+    pub length: u64,
+    /// Every step count before the walk first repeats at which it is on a goal
+    pub goal_hits: Vec<u64>,
+}
+
+impl Cycle {
+    /// Whether the walk is on a goal after `steps` steps
+    #[must_use]
+    pub fn reaches_goal_at(&self, steps: u64) -> bool {
+        let steps = if steps < self.start + self.length {
+            steps
+        } else {
+            self.start + (steps - self.start) % self.length
         };
 
-        count += 1;
+        self.goal_hits.binary_search(&steps).is_ok()
     }
+}
 
-    Ok(count)
+/// Every ghost's cycle, when they are first all on a goal together, and whether taking the
+/// LCM of the steps each needs to reach its first goal would have given the same answer
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GhostAnalysis {
+    pub cycles: Vec<Cycle>,
+    pub steps: Option<u64>,
+    pub lcm_shortcut_holds: bool,
 }
 
-#[aoc(day8, part2)]
-pub fn part2(map: &Map) -> Result<usize> {
-    let steps = map.steps.iter().cycle();
-    let current: Vec<_> = map
-        .nodes
-        .iter()
-        .filter(|(k, _)| k.ends_with('A'))
-        .map(|(_, v)| v)
-        .collect();
+impl Map {
+    /// Interns the ids of `nodes` in the order they are defined.
+    ///
+    /// # Errors
+    ///
+    /// Fails when a node is defined twice or leads to one that isn't defined.
+    fn new(steps: Steps, nodes: Vec<Node>) -> Result<Self> {
+        let mut indices = HashMap::with_capacity(nodes.len());
+        for (index, node) in nodes.iter().enumerate() {
+            if indices
+                .insert(node.id.clone(), NodeId::try_from(index)?)
+                .is_some()
+            {
+                bail!("{} is defined more than once", node.id);
+            }
+        }
 
-    // Had to find the LCM method on Reddit,
-    // I knew that the iterative solution wouldn't work,
-    // but I never seem to realize the LCM optimizations that are frequently possible for AOC
+        let lookup = |from: &Node, to: &str| {
+            indices
+                .get(to)
+                .copied()
+                .ok_or_else(|| eyre!("{} leads to {to}, which is not in the map", from.id))
+        };
+        let edges = nodes
+            .iter()
+            .map(|node| Ok([lookup(node, &node.left)?, lookup(node, &node.right)?]))
+            .collect::<Result<_>>()?;
+        let names = nodes.into_iter().map(|node| node.id).collect();
+
+        Ok(Self {
+            steps,
+            names,
+            edges,
+            indices,
+        })
+    }
 
-    let path_step = current
-        .iter()
-        .map(|&cur| {
-            let mut inner_current = cur;
-            let mut steps = steps.clone();
-            let mut count = 0_usize;
+    fn index(&self, name: &str) -> Result<NodeId, TraversalError> {
+        self.indices
+            .get(name)
+            .copied()
+            .ok_or_else(|| TraversalError::MissingNode(name.to_owned()))
+    }
+
+    fn name(&self, node: NodeId) -> &str {
+        &self.names[node as usize]
+    }
+
+    fn next(&self, node: NodeId, step: Step) -> NodeId {
+        self.edges[node as usize][step as usize]
+    }
+
+    /// How many steps it takes to walk from the node called `start` to the one called
+    /// `goal`.
+    ///
+    /// # Errors
+    ///
+    /// Fails when either node isn't in the map or the walk never reaches `goal`.
+    pub fn steps_between(&self, start: &str, goal: &str) -> Result<u64, TraversalError> {
+        let goal = self.index(goal)?;
+        let mut goals = vec![false; self.names.len()];
+        goals[goal as usize] = true;
+
+        self.first_goal(start, &goals)
+    }
+
+    /// How many steps it takes to walk from the node called `start` to the first node
+    /// that `is_goal`.
+    ///
+    /// # Errors
+    ///
+    /// Fails when `start` isn't in the map or the walk never reaches a goal.
+    pub fn steps_from(
+        &self,
+        start: &str,
+        is_goal: impl Fn(&str) -> bool,
+    ) -> Result<u64, TraversalError> {
+        self.first_goal(start, &self.goals(is_goal))
+    }
+
+    fn first_goal(&self, start: &str, goals: &[bool]) -> Result<u64, TraversalError> {
+        self.cycle_from(self.index(start)?, goals)
+            .goal_hits
+            .first()
+            .copied()
+            .ok_or_else(|| TraversalError::Unreachable {
+                starts: vec![start.to_owned()],
+            })
+    }
+
+    /// How many steps it takes for walks from every node that `is_start` to all be on a
+    /// node that `is_goal` at once.
+    ///
+    /// # Errors
+    ///
+    /// Fails when there are no starts, the walks are never all on a goal together or the
+    /// step count at which they are overflows.
+    pub fn steps_together(
+        &self,
+        is_start: impl Fn(&str) -> bool,
+        is_goal: impl Fn(&str) -> bool,
+    ) -> Result<u64, TraversalError> {
+        let analysis = self.ghost_analysis(&is_start, is_goal)?;
+        analysis.steps.ok_or_else(|| TraversalError::Unreachable {
+            starts: self.starts(is_start).map(str::to_owned).collect(),
+        })
+    }
+
+    /// Draws the network in Graphviz DOT
+    #[must_use]
+    pub fn dot(&self) -> Dot<'_> {
+        Dot {
+            map: self,
+            shown: None,
+        }
+    }
 
-            while !inner_current.id.ends_with('Z') {
-                let step = steps.next().unwrap();
+    fn starts(&self, is_start: impl Fn(&str) -> bool) -> impl Iterator<Item = &str> {
+        self.names
+            .iter()
+            .map(String::as_str)
+            .filter(move |name| is_start(name))
+            .sorted()
+    }
+
+    /// Whether each node is a goal, so that walks don't have to ask at every step
+    fn goals(&self, is_goal: impl Fn(&str) -> bool) -> Vec<bool> {
+        self.names.iter().map(|name| is_goal(name)).collect()
+    }
 
-                inner_current = match step {
-                    Step::Left => map.nodes.get(&inner_current.left).unwrap(),
-                    Step::Right => map.nodes.get(&inner_current.right).unwrap(),
+    /// Walks from `start` until it repeats a state, noting every goal on the way.
+    ///
+    /// # Errors
+    ///
+    /// Fails when `start` isn't in the map.
+    pub fn cycle(
+        &self,
+        start: &str,
+        is_goal: impl Fn(&str) -> bool,
+    ) -> Result<Cycle, TraversalError> {
+        Ok(self.cycle_from(self.index(start)?, &self.goals(is_goal)))
+    }
+
+    fn cycle_from(&self, start: NodeId, goals: &[bool]) -> Cycle {
+        let mut node = start;
+        // When each (node, step index) state was first seen
+        let mut seen = vec![None; self.names.len() * self.steps.len()];
+        let mut goal_hits = vec![];
+
+        for (count, (index, &step)) in (0..).zip(self.steps.iter().enumerate().cycle()) {
+            let state = node as usize * self.steps.len() + index;
+            if let Some(first) = seen[state] {
+                return Cycle {
+                    start: first,
+                    length: count - first,
+                    goal_hits,
                 };
+            }
+            seen[state] = Some(count);
 
-                count += 1;
+            if goals[node as usize] {
+                goal_hits.push(count);
             }
-            count
+            node = self.next(node, step);
+        }
+
+        unreachable!("there are only so many states before one repeats")
+    }
+
+    /// Works out when a ghost starting on every node that `is_start` is on a node that
+    /// `is_goal` at once. Until the last ghost has started looping every step count is
+    /// checked; after that each ghost can only be on a goal at step counts in a few
+    /// residue classes, which are combined with the Chinese remainder theorem.
+    ///
+    /// # Errors
+    ///
+    /// Fails when there are no ghosts or the step count they meet at overflows.
+    pub fn ghost_analysis(
+        &self,
+        is_start: impl Fn(&str) -> bool,
+        is_goal: impl Fn(&str) -> bool,
+    ) -> Result<GhostAnalysis, TraversalError> {
+        let goals = self.goals(is_goal);
+        let cycles: Vec<Cycle> = self
+            .starts(is_start)
+            .map(|name| Ok(self.cycle_from(self.index(name)?, &goals)))
+            .collect::<Result<_, TraversalError>>()?;
+        let first = cycles.first().ok_or(TraversalError::NoStart)?;
+
+        let settled = cycles.iter().map(|cycle| cycle.start).max().unwrap_or(0);
+        let steps = match (0..settled)
+            .filter(|&steps| first.reaches_goal_at(steps))
+            .find(|&steps| cycles.iter().all(|cycle| cycle.reaches_goal_at(steps)))
+        {
+            Some(steps) => Some(steps),
+            None => steps_after_settling(&cycles, settled)?,
+        };
+
+        // An LCM that overflows can't be the answer, so the shortcut doesn't hold
+        let shortcut = cycles
+            .iter()
+            .map(|cycle| cycle.goal_hits.first().copied())
+            .try_fold(1, |acc, hit| checked_lcm(acc, hit?));
+
+        Ok(GhostAnalysis {
+            lcm_shortcut_holds: steps.is_some() && steps == shortcut,
+            cycles,
+            steps,
         })
-        .fold(None, |acc, num| {
-            if let Some(acc) = acc {
-                Some(lcm(acc, num))
-            } else {
-                Some(num)
-            }
-        });
+    }
+}
+
+/// The first step count from `settled` on at which every ghost is on a goal, found by
+/// combining the residue classes of each ghost's goals in its loop
+fn steps_after_settling(cycles: &[Cycle], settled: u64) -> Result<Option<u64>, TraversalError> {
+    let residues = cycles
+        .iter()
+        .map(|cycle| {
+            cycle
+                .goal_hits
+                .iter()
+                .filter(|&&hit| hit >= cycle.start)
+                .map(|&hit| (u128::from(hit % cycle.length), u128::from(cycle.length)))
+                .collect_vec()
+        })
+        .multi_cartesian_product();
 
-    path_step.ok_or(eyre!("no valid items"))
+    let mut first: Option<u128> = None;
+    for residues in residues {
+        let combined = residues
+            .into_iter()
+            .try_fold(Some((0, 1)), |acc, residue| {
+                acc.map_or(Ok(None), |acc| crt(acc, residue))
+            })?;
+
+        if let Some((residue, modulus)) = combined {
+            let steps =
+                at_least(u128::from(settled), residue, modulus).ok_or(TraversalError::Overflow)?;
+            first = Some(first.map_or(steps, |first| first.min(steps)));
+        }
+    }
+
+    first
+        .map(|steps| u64::try_from(steps).map_err(|_| TraversalError::Overflow))
+        .transpose()
+}
+
+/// Combines `x = a mod m` and `x = b mod n` into a single residue class, or `None` when
+/// no number is in both.
+///
+/// # Errors
+///
+/// Fails when the combined modulus doesn't fit in a `u128`.
+#[allow(clippy::many_single_char_names)]
+fn crt((a, m): (u128, u128), (b, n): (u128, u128)) -> Result<Option<(u128, u128)>, TraversalError> {
+    let overflow = |_| TraversalError::Overflow;
+    let (g, p, _) = extended_gcd(
+        i128::try_from(m).map_err(overflow)?,
+        i128::try_from(n).map_err(overflow)?,
+    );
+    let g = g.unsigned_abs();
+    let gap = b.abs_diff(a);
+    if gap % g != 0 {
+        return Ok(None);
+    }
+
+    // x = a + k * m, where k * m = b - a mod n
+    let step = n / g;
+    let gap = if b >= a {
+        gap / g % step
+    } else {
+        (step - gap / g % step) % step
+    };
+    let inverse = p
+        .rem_euclid(i128::try_from(step).map_err(overflow)?)
+        .unsigned_abs();
+    let k = gap.checked_mul(inverse).ok_or(TraversalError::Overflow)? % step;
+    let modulus = (m / g).checked_mul(n).ok_or(TraversalError::Overflow)?;
+    let x = k
+        .checked_mul(m)
+        .and_then(|x| x.checked_add(a))
+        .ok_or(TraversalError::Overflow)?;
+
+    Ok(Some((x % modulus, modulus)))
+}
+
+/// The least common multiple of `a` and `b`, or `None` when it doesn't fit in a `u64`
+fn checked_lcm(a: u64, b: u64) -> Option<u64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / a.gcd(&b)).checked_mul(b)
+}
+
+/// `(g, p, q)` where `g = gcd(a, b) = p * a + q * b`
+#[allow(clippy::many_single_char_names)]
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, p, q) = extended_gcd(b, a % b);
+        (g, q, p - a / b * q)
+    }
+}
+
+/// The smallest number that is at least `floor` and is `residue` mod `modulus`, or `None`
+/// when it overflows
+fn at_least(floor: u128, residue: u128, modulus: u128) -> Option<u128> {
+    if residue >= floor {
+        Some(residue)
+    } else {
+        (floor - residue)
+            .div_ceil(modulus)
+            .checked_mul(modulus)?
+            .checked_add(residue)
+    }
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 enum Step {
-    Left,
-    Right,
+    Left = 0,
+    Right = 1,
 }
 
 type Steps = Vec<Step>;
@@ -88,15 +435,94 @@ struct Node {
     right: String,
 }
 
+/// A node's index into the tables of a [`Map`]
+type NodeId = u32;
+
+/// The network, with every node id interned into a dense index so that walking it is
+/// just indexing into `edges`. `names` turns an index back into its id.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Map {
     steps: Steps,
-    nodes: HashMap<String, Node>,
+    names: Vec<String>,
+    /// The left and right neighbours of each node
+    edges: Vec<[NodeId; 2]>,
+    indices: HashMap<String, NodeId>,
 }
 
-mod parsers {
-    use std::collections::HashMap;
+/// The network in Graphviz DOT, with each edge labelled with the step that takes it. Ghost
+/// starts (`..A`) are drawn as green boxes and goals (`..Z`) as red double circles. Unless
+/// it is narrowed down with [`reachable_from`](Dot::reachable_from) every node is drawn.
+pub struct Dot<'a> {
+    map: &'a Map,
+    /// Which nodes to draw, or all of them when `None`
+    shown: Option<Vec<bool>>,
+}
+
+impl Dot<'_> {
+    /// Leaves out every node that can't be reached from one of `starts`
+    ///
+    /// # Errors
+    ///
+    /// Fails when one of `starts` isn't in the map.
+    pub fn reachable_from(self, starts: &[&str]) -> Result<Self, TraversalError> {
+        let mut shown = vec![false; self.map.names.len()];
+        let mut queue: Vec<NodeId> = starts
+            .iter()
+            .map(|start| self.map.index(start))
+            .try_collect()?;
+
+        while let Some(node) = queue.pop() {
+            if !std::mem::replace(&mut shown[node as usize], true) {
+                queue.extend(self.map.edges[node as usize]);
+            }
+        }
+
+        Ok(Self {
+            shown: Some(shown),
+            ..self
+        })
+    }
+
+    fn is_shown(&self, node: NodeId) -> bool {
+        self.shown.as_ref().is_none_or(|shown| shown[node as usize])
+    }
+}
+
+impl Display for Dot<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let nodes = (0..)
+            .zip(&self.map.names)
+            .filter(|&(node, _)| self.is_shown(node));
+
+        writeln!(f, "digraph network {{")?;
+        for (_, name) in nodes.clone() {
+            let style = if is_ghost_start(name) {
+                " [shape=box, style=filled, fillcolor=palegreen]"
+            } else if is_ghost_goal(name) {
+                " [shape=doublecircle, style=filled, fillcolor=salmon]"
+            } else {
+                ""
+            };
+            writeln!(f, "    \"{name}\"{style};")?;
+        }
+
+        for (node, name) in nodes {
+            let [left, right] = self.map.edges[node as usize];
+            if left == right {
+                let to = self.map.name(left);
+                writeln!(f, "    \"{name}\" -> \"{to}\" [label=\"L/R\"];")?;
+            } else {
+                for (to, label) in [(left, "L"), (right, "R")] {
+                    let to = self.map.name(to);
+                    writeln!(f, "    \"{name}\" -> \"{to}\" [label=\"{label}\"];")?;
+                }
+            }
+        }
+        writeln!(f, "}}")
+    }
+}
 
+mod parsers {
     use color_eyre::eyre::eyre;
     use nom::{
         branch::alt,
@@ -112,27 +538,14 @@ mod parsers {
     use crate::day8::{Map, Node, Step, Steps};
 
     pub(crate) fn parse_input(input: &str) -> color_eyre::Result<Map> {
-        let (_, map) = all_consuming(map)(input)
+        let (_, (steps, nodes)) = all_consuming(map)(input)
             .map_err(|e| e.to_owned())
             .finish()?;
-        Ok(map)
+        Map::new(steps, nodes)
     }
 
-    fn map(input: &str) -> IResult<&str, Map> {
-        combinator::map(
-            separated_pair(terminated(steps, newline), newline, nodes),
-            |(steps, nodes)| {
-                let node_map: HashMap<String, Node> = nodes
-                    .into_iter()
-                    .map(|node| (node.id.clone(), node))
-                    .collect();
-
-                Map {
-                    steps,
-                    nodes: node_map,
-                }
-            },
-        )(input)
+    fn map(input: &str) -> IResult<&str, (Steps, Vec<Node>)> {
+        separated_pair(terminated(steps, newline), newline, nodes)(input)
     }
 
     fn steps(input: &str) -> IResult<&str, Steps> {
@@ -178,7 +591,8 @@ mod parsers {
 #[cfg(test)]
 mod tests {
     use color_eyre::Result;
-    use itertools::Itertools;
+    use indoc::indoc;
+    use rstest::rstest;
 
     use super::*;
 
@@ -192,19 +606,47 @@ EEE = (EEE, EEE)
 GGG = (GGG, GGG)
 ZZZ = (ZZZ, ZZZ)";
 
+    const PART2_SAMPLE: &str = "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)";
+
     #[test]
     fn test_parser() -> Result<()> {
         let map = input_generator(SAMPLE_INPUT)?;
         assert_eq!(vec![Step::Right, Step::Left], map.steps);
         let nodes: Vec<_> = map
-            .nodes
-            .into_iter()
+            .names
+            .iter()
+            .zip(&map.edges)
+            .map(|(id, &[left, right])| {
+                let node = Node {
+                    id: id.clone(),
+                    left: map.name(left).to_owned(),
+                    right: map.name(right).to_owned(),
+                };
+                (id.clone(), node)
+            })
             .sorted_by_key(|(k, _)| k.clone())
             .collect();
         insta::assert_debug_snapshot!(nodes);
         Ok(())
     }
 
+    #[rstest]
+    #[case("AAA = (BBB, AAA)", "AAA leads to BBB, which is not in the map")]
+    #[case("AAA = (AAA, AAA)\nAAA = (AAA, AAA)", "AAA is defined more than once")]
+    fn parser_rejects_broken_networks(#[case] nodes: &str, #[case] expected: &str) {
+        let error = input_generator(&format!("L\n\n{nodes}")).unwrap_err();
+        assert_eq!(expected, error.to_string());
+    }
+
     #[test]
     fn part1_sample_input() -> Result<()> {
         let map = input_generator(SAMPLE_INPUT)?;
@@ -230,21 +672,247 @@ ZZZ = (ZZZ, ZZZ)";
 
     #[test]
     fn part2_example_input() -> Result<()> {
-        let sample = "LR
+        let map = input_generator(PART2_SAMPLE)?;
+        let actual = part2(&map)?;
+
+        assert_eq!(6, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn part2_lcm_shortcut_holds_on_the_example() -> Result<()> {
+        let map = input_generator(PART2_SAMPLE)?;
+        let analysis = ghosts(&map)?;
+
+        assert_eq!(Some(6), analysis.steps);
+        assert!(analysis.lcm_shortcut_holds);
+        assert_eq!(
+            vec![
+                Cycle {
+                    start: 1,
+                    length: 2,
+                    goal_hits: vec![2]
+                },
+                Cycle {
+                    start: 1,
+                    length: 6,
+                    goal_hits: vec![3, 6]
+                },
+            ],
+            analysis.cycles
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn part2_when_the_lcm_shortcut_fails() -> Result<()> {
+        // 11A is on 11Z every other step from 2, and 22A every third step from 1, so
+        // the LCM of their first goals, 2, is wrong
+        let sample = "L
 
 11A = (11B, XXX)
-11B = (XXX, 11Z)
+11B = (11Z, XXX)
 11Z = (11B, XXX)
-22A = (22B, XXX)
-22B = (22C, 22C)
-22C = (22Z, 22Z)
-22Z = (22B, 22B)
+22A = (22Z, XXX)
+22Z = (22B, XXX)
+22B = (22C, XXX)
+22C = (22Z, XXX)
 XXX = (XXX, XXX)";
 
         let map = input_generator(sample)?;
-        let actual = part2(&map)?;
+        let analysis = ghosts(&map)?;
 
-        assert_eq!(6, actual);
+        assert_eq!(Some(4), analysis.steps);
+        assert!(!analysis.lcm_shortcut_holds);
+        assert_eq!(4, part2(&map)?);
+        Ok(())
+    }
+
+    #[test]
+    fn part2_when_the_ghosts_never_meet() -> Result<()> {
+        let sample = "L
+
+11A = (11B, XXX)
+11B = (11Z, XXX)
+11Z = (11B, XXX)
+33A = (33Z, XXX)
+33Z = (33B, XXX)
+33B = (33Z, XXX)
+XXX = (XXX, XXX)";
+
+        let map = input_generator(sample)?;
+
+        assert_eq!(None, ghosts(&map)?.steps);
+        assert_eq!(
+            Err(TraversalError::Unreachable {
+                starts: vec!["11A".to_owned(), "33A".to_owned()]
+            }),
+            map.steps_together(is_ghost_start, is_ghost_goal)
+        );
+        assert!(part2(&map).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn part2_goals_before_every_ghost_loops() -> Result<()> {
+        // 44A only passes its goal once, on its way into a loop without one
+        let sample = "L
+
+11A = (11Z, XXX)
+11Z = (11Z, XXX)
+44A = (44Z, XXX)
+44Z = (44B, XXX)
+44B = (44B, XXX)
+XXX = (XXX, XXX)";
+
+        let map = input_generator(sample)?;
+        let analysis = ghosts(&map)?;
+
+        assert_eq!(Some(1), analysis.steps);
+        assert!(analysis.lcm_shortcut_holds);
+        Ok(())
+    }
+
+    fn ghosts(map: &Map) -> Result<GhostAnalysis, TraversalError> {
+        map.ghost_analysis(is_ghost_start, is_ghost_goal)
+    }
+
+    #[rstest]
+    #[case("AAA", "ZZZ", Ok(2))]
+    #[case("AAA", "QQQ", Err(TraversalError::MissingNode("QQQ".to_owned())))]
+    #[case("QQQ", "ZZZ", Err(TraversalError::MissingNode("QQQ".to_owned())))]
+    #[case("DDD", "ZZZ", Err(TraversalError::Unreachable { starts: vec!["DDD".to_owned()] }))]
+    #[case("BBB", "EEE", Ok(1))]
+    fn test_steps_between(
+        #[case] start: &str,
+        #[case] goal: &str,
+        #[case] expected: Result<u64, TraversalError>,
+    ) -> Result<()> {
+        let map = input_generator(SAMPLE_INPUT)?;
+
+        assert_eq!(expected, map.steps_between(start, goal));
+        Ok(())
+    }
+
+    #[test]
+    fn steps_from_stops_at_the_first_goal() -> Result<()> {
+        let map = input_generator(SAMPLE_INPUT)?;
+
+        assert_eq!(Ok(1), map.steps_from("AAA", |name| name != "AAA"));
+        Ok(())
+    }
+
+    #[test]
+    fn steps_together_without_starts() -> Result<()> {
+        let map = input_generator(SAMPLE_INPUT)?;
+
+        assert_eq!(
+            Err(TraversalError::NoStart),
+            map.steps_together(|name| name.starts_with('Q'), is_ghost_goal)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_dot() -> Result<()> {
+        let map = input_generator(SAMPLE_INPUT)?;
+
+        assert_eq!(
+            indoc! {r#"
+            digraph network {
+                "AAA" [shape=box, style=filled, fillcolor=palegreen];
+                "BBB";
+                "CCC";
+                "DDD";
+                "EEE";
+                "GGG";
+                "ZZZ" [shape=doublecircle, style=filled, fillcolor=salmon];
+                "AAA" -> "BBB" [label="L"];
+                "AAA" -> "CCC" [label="R"];
+                "BBB" -> "DDD" [label="L"];
+                "BBB" -> "EEE" [label="R"];
+                "CCC" -> "ZZZ" [label="L"];
+                "CCC" -> "GGG" [label="R"];
+                "DDD" -> "DDD" [label="L/R"];
+                "EEE" -> "EEE" [label="L/R"];
+                "GGG" -> "GGG" [label="L/R"];
+                "ZZZ" -> "ZZZ" [label="L/R"];
+            }
+            "#},
+            map.dot().to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_dot_reachable_from() -> Result<()> {
+        let map = input_generator(PART2_SAMPLE)?;
+
+        assert_eq!(
+            indoc! {r#"
+            digraph network {
+                "11A" [shape=box, style=filled, fillcolor=palegreen];
+                "11B";
+                "11Z" [shape=doublecircle, style=filled, fillcolor=salmon];
+                "XXX";
+                "11A" -> "11B" [label="L"];
+                "11A" -> "XXX" [label="R"];
+                "11B" -> "XXX" [label="L"];
+                "11B" -> "11Z" [label="R"];
+                "11Z" -> "11B" [label="L"];
+                "11Z" -> "XXX" [label="R"];
+                "XXX" -> "XXX" [label="L/R"];
+            }
+            "#},
+            map.dot().reachable_from(&["11A"])?.to_string()
+        );
+        assert_eq!(
+            Some(TraversalError::MissingNode("QQQ".to_owned())),
+            map.dot().reachable_from(&["QQQ"]).err()
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case((2, 3), (3, 5), Ok(Some((8, 15))))]
+    #[case((0, 4), (2, 6), Ok(Some((8, 12))))]
+    #[case((3, 5), (2, 3), Ok(Some((8, 15))))]
+    #[case((0, 2), (1, 4), Ok(None))]
+    #[case((0, 1 << 100), (1, (1 << 100) + 1), Err(TraversalError::Overflow))]
+    fn test_crt(
+        #[case] a: (u128, u128),
+        #[case] b: (u128, u128),
+        #[case] expected: Result<Option<(u128, u128)>, TraversalError>,
+    ) {
+        assert_eq!(expected, crt(a, b));
+    }
+
+    #[rstest]
+    #[case(4, 6, Some(12))]
+    #[case(1, 0, Some(0))]
+    #[case(u64::MAX, u64::MAX - 1, None)]
+    fn test_checked_lcm(#[case] a: u64, #[case] b: u64, #[case] expected: Option<u64>) {
+        assert_eq!(expected, checked_lcm(a, b));
+    }
+
+    #[test]
+    fn part2_repeated_goals_before_every_ghost_loops() -> Result<()> {
+        // 11A is on a goal at every odd step, while 55A is only on one at step 3, just
+        // before it starts looping
+        let sample = "L
+
+11A = (11Z, XXX)
+11Z = (11A, XXX)
+55A = (55B, XXX)
+55B = (55C, XXX)
+55C = (55Z, XXX)
+55Z = (55D, XXX)
+55D = (55D, XXX)
+XXX = (XXX, XXX)";
+
+        let map = input_generator(sample)?;
+
+        assert_eq!(Some(3), ghosts(&map)?.steps);
         Ok(())
     }
 }
@@ -1,7 +1,12 @@
 use aoc_runner_derive::{aoc, aoc_generator};
-use color_eyre::{Report, Result};
-use itertools::Itertools;
+use std::fmt::{Display, Formatter};
+
+use color_eyre::{
+    eyre::{bail, eyre},
+    Report, Result,
+};
 use nom::{combinator::all_consuming, Finish};
+use num::{bigint::BigInt, BigRational, One, Signed, Zero};
 
 use crate::day9::parsers::scan_line;
 
@@ -12,12 +17,12 @@ pub fn input_generator(input: &str) -> Result<Scan> {
 
 #[aoc(day9, part1)]
 pub fn part1(input: &Scan) -> Result<i64> {
-    Ok(input.lines.iter().map(|line| line.next_value()).sum())
+    input.sum(|line| line.forward(1))
 }
 
 #[aoc(day9, part2)]
 pub fn part2(input: &Scan) -> Result<i64> {
-    Ok(input.lines.iter().map(|line| line.first_value()).sum())
+    input.sum(|line| line.backward(1))
 }
 
 #[derive(Debug, Clone)]
@@ -25,74 +30,222 @@ pub struct Scan {
     lines: Vec<ScanLine>,
 }
 
+impl Scan {
+    #[must_use]
+    pub fn lines(&self) -> &[ScanLine] {
+        &self.lines
+    }
+
+    fn sum(&self, value: impl Fn(&ScanLine) -> Result<i128>) -> Result<i64> {
+        let sum = self.lines.iter().try_fold(0i128, |sum, line| {
+            sum.checked_add(value(line)?)
+                .ok_or_else(|| eyre!("the sum of the extrapolated values overflows"))
+        })?;
+
+        Ok(i64::try_from(sum)?)
+    }
+}
+
 #[derive(Debug, Clone)]
-struct ScanLine {
+pub struct ScanLine {
     values: Vec<i64>,
 }
 
 impl ScanLine {
-    fn sequences(&self) -> Vec<Vec<i64>> {
-        let mut current = self.values.clone();
-        let mut sequences = vec![];
-
-        while !current.iter().all(|n| n == &0) {
-            let new_current = current
-                .iter()
-                .tuple_windows()
-                .fold(Vec::new(), |mut acc, (x, y)| {
-                    acc.push(y - x);
-                    acc
-                });
-            sequences.push(current);
-            current = new_current;
+    /// The first value of each row of the difference triangle, that is `Δ^j f(0)` for
+    /// every `j` below the number of values. They are worked out in place, one row at a
+    /// time, without building the triangle.
+    fn differences(&self) -> Result<Vec<i128>> {
+        let mut differences: Vec<i128> = self.values.iter().copied().map(i128::from).collect();
+
+        for row in 1..differences.len() {
+            for i in (row..differences.len()).rev() {
+                differences[i] = differences[i]
+                    .checked_sub(differences[i - 1])
+                    .ok_or_else(|| eyre!("the differences overflow"))?;
+            }
+        }
+
+        Ok(differences)
+    }
+
+    /// The differences up to the last one that isn't zero, which are all that
+    /// extrapolating needs.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the difference triangle never reaches a row of zeros, as then the
+    /// values don't pin down what comes next, or when the differences overflow.
+    fn newton_differences(&self) -> Result<Vec<i128>> {
+        let mut differences = self.differences()?;
+        let Some(degree) = differences.iter().rposition(|&d| d != 0) else {
+            // Every value is zero, so the first row already is a row of zeros
+            return Ok(vec![0]);
+        };
+        if degree + 1 >= differences.len() {
+            bail!(
+                "the differences of {:?} never reach a row of zeros",
+                self.values
+            );
         }
-        sequences.push(current);
 
-        sequences
+        differences.truncate(degree + 1);
+        Ok(differences)
     }
 
-    fn next_value(&self) -> i64 {
-        let mut sequences = self.sequences();
+    /// The value at position `x`, where the recorded values are at `0..n`, from Newton's
+    /// forward-difference formula `f(x) = Σ C(x, j) Δ^j f(0)`. `C(x, j)` is the binomial
+    /// coefficient generalised to any integer `x`, so this works both ways.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the difference triangle never reaches a row of zeros or the arithmetic
+    /// overflows an `i128`.
+    pub fn value_at(&self, x: i128) -> Result<i128> {
+        let overflow = || eyre!("extrapolating to {x} overflows");
+        let differences = self.newton_differences()?;
+
+        let mut value = 0i128;
+        // C(x, j), kept exact by C(x, j) = C(x, j - 1) * (x - j + 1) / j. It is only
+        // advanced when there is a difference to use it with, as the next one could overflow.
+        let mut binomial = 1i128;
+
+        for (j, difference) in (0i128..).zip(differences) {
+            if j > 0 {
+                let factor = x.checked_sub(j - 1).ok_or_else(overflow)?;
+                binomial = binomial.checked_mul(factor).ok_or_else(overflow)? / j;
+            }
+            let term = binomial.checked_mul(difference).ok_or_else(overflow)?;
+            value = value.checked_add(term).ok_or_else(overflow)?;
+        }
+
+        Ok(value)
+    }
 
-        sequences
-            .iter_mut()
-            .rev()
-            .fold(None, |prev: Option<&mut Vec<i64>>, seq| {
-                if let Some(prev) = prev {
-                    let last_prev = prev.last().unwrap();
-                    let last_seq = seq.last().unwrap();
+    /// The value `steps` after the last recorded one
+    ///
+    /// # Errors
+    ///
+    /// Fails when the difference triangle never reaches a row of zeros or the arithmetic
+    /// overflows an `i128`.
+    pub fn forward(&self, steps: u64) -> Result<i128> {
+        let last = i128::try_from(self.values.len())? - 1;
+        self.value_at(last + i128::from(steps))
+    }
 
-                    seq.push(last_seq + last_prev);
+    /// The value `steps` before the first recorded one
+    ///
+    /// # Errors
+    ///
+    /// Fails when the difference triangle never reaches a row of zeros or the arithmetic
+    /// overflows an `i128`.
+    pub fn backward(&self, steps: u64) -> Result<i128> {
+        self.value_at(-i128::from(steps))
+    }
+
+    /// The polynomial of lowest degree that goes through every recorded value, with
+    /// exact coefficients. It is recovered from Newton's forward-difference formula by
+    /// expanding each `C(x, j)` into powers of `x`.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the difference triangle never reaches a row of zeros or the differences
+    /// overflow an `i128`.
+    pub fn polynomial(&self) -> Result<Polynomial> {
+        let differences = self.newton_differences()?;
+        let mut coefficients = vec![BigRational::zero(); differences.len()];
+        // x (x - 1) ... (x - j + 1) / j!, as coefficients of increasing powers of x
+        let mut falling = vec![BigRational::one()];
+
+        for (j, difference) in (0..).zip(&differences) {
+            let difference = BigRational::from_integer(BigInt::from(*difference));
+            for (coefficient, term) in coefficients.iter_mut().zip(&falling) {
+                *coefficient += term * &difference;
+            }
+
+            let j = BigRational::from_integer(BigInt::from(j));
+            let next = &j + BigRational::one();
+            falling.push(BigRational::zero());
+            for k in (0..falling.len()).rev() {
+                let lower = if k > 0 {
+                    falling[k - 1].clone()
                 } else {
-                    seq.push(0);
-                }
+                    BigRational::zero()
+                };
+                falling[k] = (lower - &falling[k] * &j) / &next;
+            }
+        }
 
-                Some(seq)
-            });
+        Ok(Polynomial { coefficients })
+    }
+}
+
+/// A polynomial with exact rational coefficients
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Polynomial {
+    /// The coefficient of each power of `x`, from the constant up
+    coefficients: Vec<BigRational>,
+}
 
-        *sequences.first().unwrap().last().unwrap()
+impl Polynomial {
+    #[must_use]
+    pub fn degree(&self) -> usize {
+        self.coefficients.len() - 1
     }
 
-    fn first_value(&self) -> i64 {
-        let mut sequences = self.sequences();
+    #[must_use]
+    pub fn coefficients(&self) -> &[BigRational] {
+        &self.coefficients
+    }
 
-        sequences
-            .iter_mut()
+    #[must_use]
+    pub fn evaluate(&self, x: &BigRational) -> BigRational {
+        self.coefficients
+            .iter()
             .rev()
-            .fold(None, |prev: Option<&mut Vec<i64>>, seq| {
-                if let Some(prev) = prev {
-                    let first_prev = prev.first().unwrap();
-                    let first_seq = seq.first().unwrap();
+            .fold(BigRational::zero(), |value, coefficient| {
+                value * x + coefficient
+            })
+    }
+}
 
-                    seq.insert(0, first_seq - first_prev);
-                } else {
-                    seq.insert(0, 0);
+impl Display for Polynomial {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let terms = (0..self.coefficients.len())
+            .rev()
+            .zip(self.coefficients.iter().rev())
+            .filter(|(_, coefficient)| !coefficient.is_zero());
+        let mut empty = true;
+
+        for (power, coefficient) in terms {
+            let sign = if coefficient.is_negative() { "-" } else { "+" };
+            if empty {
+                if coefficient.is_negative() {
+                    f.write_str(sign)?;
                 }
+            } else {
+                write!(f, " {sign} ")?;
+            }
+            empty = false;
+
+            let magnitude = coefficient.abs();
+            match (power, magnitude.is_integer()) {
+                (0, _) => write!(f, "{magnitude}")?,
+                (_, true) if magnitude.is_one() => {}
+                (_, true) => write!(f, "{magnitude}")?,
+                (_, false) => write!(f, "({magnitude})")?,
+            }
+            match power {
+                0 => {}
+                1 => f.write_str("x")?,
+                _ => write!(f, "x^{power}")?,
+            }
+        }
 
-                Some(seq)
-            });
-
-        *sequences.first().unwrap().first().unwrap()
+        if empty {
+            f.write_str("0")?;
+        }
+        Ok(())
     }
 }
 
@@ -168,13 +321,16 @@ mod tests {
     }
 
     #[rstest]
-    #[case::first_history("0 3 6 9 12 15")]
-    #[case::second_history("1 3 6 10 15 21")]
-    #[case::third_history("10 13 16 21  30 45")]
-    fn sequences_from_examples(#[case] example_history: &str) -> Result<()> {
+    #[case::first_history("0 3 6 9 12 15", vec![0, 3, 0, 0, 0, 0])]
+    #[case::second_history("1 3 6 10 15 21", vec![1, 2, 1, 0, 0, 0])]
+    #[case::third_history("10 13 16 21  30 45", vec![10, 3, 0, 2, 0, 0])]
+    fn differences_from_examples(
+        #[case] example_history: &str,
+        #[case] expected: Vec<i128>,
+    ) -> Result<()> {
         let scan_line: ScanLine = example_history.try_into()?;
-        let sequences = scan_line.sequences();
-        insta::assert_debug_snapshot!(example_history, sequences);
+
+        assert_eq!(expected, scan_line.differences()?);
         Ok(())
     }
 
@@ -184,10 +340,10 @@ mod tests {
     #[case::third_history("10 13 16 21  30 45", 68)]
     fn next_history_from_examples(
         #[case] example_history: &str,
-        #[case] expected: i64,
+        #[case] expected: i128,
     ) -> Result<()> {
         let scan_line: ScanLine = example_history.try_into()?;
-        let next_history = scan_line.next_value();
+        let next_history = scan_line.forward(1)?;
 
         assert_eq!(expected, next_history);
         Ok(())
@@ -199,15 +355,98 @@ mod tests {
     #[case::third_history("10 13 16 21  30 45", 5)]
     fn first_history_from_examples(
         #[case] example_history: &str,
-        #[case] expected: i64,
+        #[case] expected: i128,
     ) -> Result<()> {
         let scan_line: ScanLine = example_history.try_into()?;
-        let next_history = scan_line.first_value();
+        let next_history = scan_line.backward(1)?;
 
         assert_eq!(expected, next_history);
         Ok(())
     }
 
+    #[rstest]
+    #[case::linear("0 3 6 9 12 15", 1_000_000_000, 3_000_000_015, -3_000_000_000)]
+    #[case::quadratic("1 3 6 10 15 21", 10, 136, 36)]
+    #[case::cubic("0 1 8 27 64", 5, 729, -125)]
+    #[case::single_zero("0", 3, 0, 0)]
+    fn extrapolate_further(
+        #[case] history: &str,
+        #[case] steps: u64,
+        #[case] forward: i128,
+        #[case] backward: i128,
+    ) -> Result<()> {
+        let scan_line: ScanLine = history.try_into()?;
+
+        assert_eq!(forward, scan_line.forward(steps)?);
+        assert_eq!(backward, scan_line.backward(steps)?);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::linear("0 3 6 9 12 15", &[(0, 1), (3, 1)], "3x")]
+    #[case::quadratic("1 3 6 10 15 21", &[(1, 1), (3, 2), (1, 2)], "(1/2)x^2 + (3/2)x + 1")]
+    #[case::cubic(
+        "10 13 16 21  30 45",
+        &[(10, 1), (11, 3), (-1, 1), (1, 3)],
+        "(1/3)x^3 - x^2 + (11/3)x + 10"
+    )]
+    #[case::constant("-4 -4 -4", &[(-4, 1)], "-4")]
+    #[case::zero("0 0", &[(0, 1)], "0")]
+    #[case::single_zero("0", &[(0, 1)], "0")]
+    fn recover_polynomial(
+        #[case] history: &str,
+        #[case] coefficients: &[(i64, i64)],
+        #[case] display: &str,
+    ) -> Result<()> {
+        let scan_line: ScanLine = history.try_into()?;
+        let polynomial = scan_line.polynomial()?;
+
+        let expected: Vec<_> = coefficients
+            .iter()
+            .map(|&(numerator, denominator)| BigRational::new(numerator.into(), denominator.into()))
+            .collect();
+        assert_eq!(expected, polynomial.coefficients());
+        assert_eq!(coefficients.len() - 1, polynomial.degree());
+        assert_eq!(display, polynomial.to_string());
+
+        for (x, &value) in (0i64..).zip(&scan_line.values) {
+            let x = BigRational::from_integer(x.into());
+            assert_eq!(
+                BigRational::from_integer(value.into()),
+                polynomial.evaluate(&x)
+            );
+        }
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::single_value("7")]
+    #[case::powers_of_two("1 2 4 8 16")]
+    fn extrapolation_needs_a_row_of_zeros(#[case] history: &str) -> Result<()> {
+        let scan_line: ScanLine = history.try_into()?;
+
+        assert!(scan_line.polynomial().is_err());
+        assert!(scan_line.forward(1).is_err());
+        assert!(scan_line.backward(1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn extrapolate_linear_to_the_largest_offset() -> Result<()> {
+        let scan_line: ScanLine = "0 3 6 9 12 15".try_into()?;
+
+        assert_eq!(3 * (i128::from(u64::MAX) + 5), scan_line.forward(u64::MAX)?);
+        Ok(())
+    }
+
+    #[test]
+    fn extrapolation_overflow_is_an_error() -> Result<()> {
+        let scan_line: ScanLine = "0 1 8 27 64".try_into()?;
+
+        assert!(scan_line.forward(u64::MAX).is_err());
+        Ok(())
+    }
+
     #[test]
     fn part2_sample_input() -> Result<()> {
         let scan = input_generator(SAMPLE_INPUT)?;
@@ -1,6 +1,7 @@
 #![warn(clippy::pedantic)]
 use aoc_runner_derive::aoc_lib;
 
+pub mod runner;
 pub mod utils;
 
 pub mod day1;
@@ -10,7 +11,7 @@ pub mod day4;
 
 pub mod day10;
 pub mod day11;
-mod day12;
+pub mod day12;
 pub mod day5;
 pub mod day6;
 pub mod day7;
@@ -0,0 +1,152 @@
+use std::{
+    error::Error,
+    time::{Duration, Instant},
+};
+
+use aoc_runner::{ArcStr, Runner};
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Report, Result,
+};
+use itertools::Itertools;
+
+#[allow(clippy::wildcard_imports)]
+use crate::*;
+
+type Factory = fn(ArcStr) -> std::result::Result<Box<dyn Runner>, Box<dyn Error>>;
+
+/// A day and part registered with `#[aoc]`, runnable without `cargo aoc`
+#[derive(Clone, Copy)]
+pub struct Solution {
+    pub day: u32,
+    pub part: u32,
+    factory: Factory,
+}
+
+/// The answer a solution gave along with how long generating and solving took
+#[derive(Debug, Clone)]
+pub struct Answer {
+    pub value: String,
+    pub generator: Duration,
+    pub solver: Duration,
+}
+
+impl Solution {
+    /// Generates the input for this solution and solves it.
+    ///
+    /// # Errors
+    ///
+    /// Fails when either the generator or the solver fails.
+    pub fn run(&self, input: &str) -> Result<Answer> {
+        let start = Instant::now();
+        let runner = (self.factory)(ArcStr::from(input))
+            .map_err(|e| to_report(&*e))
+            .wrap_err_with(|| self.failure("generating"))?;
+
+        let generated = Instant::now();
+        let value = runner
+            .try_run()
+            .map_err(|e| to_report(&*e))
+            .wrap_err_with(|| self.failure("running"))?
+            .to_string();
+
+        Ok(Answer {
+            value,
+            generator: generated - start,
+            solver: generated.elapsed(),
+        })
+    }
+
+    fn failure(&self, stage: &str) -> String {
+        format!("Day {} - Part {} failed while {stage}", self.day, self.part)
+    }
+}
+
+/// The runner hands back errors as plain boxed errors, so rebuild the chain of messages
+fn to_report(error: &(dyn Error + 'static)) -> Report {
+    let message = std::iter::successors(Some(error), |&e| e.source()).join(": ");
+
+    eyre!(message)
+}
+
+macro_rules! solutions {
+    ($(($day:literal, $part:literal, $registration:ident :: $factory:ident)),* $(,)?) => {
+        /// Every registered solution, ordered by day then part
+        pub static SOLUTIONS: &[Solution] = &[
+            $(Solution {
+                day: $day,
+                part: $part,
+                factory: <crate::Factory as $registration>::$factory,
+            }),*
+        ];
+    };
+}
+
+solutions![
+    (1, 1, Day1Part1::day1_part1),
+    (1, 2, Day1Part2::day1_part2),
+    (2, 1, Day2Part1::day2_part1),
+    (2, 2, Day2Part2::day2_part2),
+    (3, 1, Day3Part1::day3_part1),
+    (3, 2, Day3Part2::day3_part2),
+    (4, 1, Day4Part1::day4_part1),
+    (4, 2, Day4Part2::day4_part2),
+    (5, 1, Day5Part1::day5_part1),
+    (5, 2, Day5Part2::day5_part2),
+    (6, 1, Day6Part1::day6_part1),
+    (6, 2, Day6Part2::day6_part2),
+    (7, 1, Day7Part1::day7_part1),
+    (7, 2, Day7Part2::day7_part2),
+    (8, 1, Day8Part1::day8_part1),
+    (8, 2, Day8Part2::day8_part2),
+    (9, 1, Day9Part1::day9_part1),
+    (9, 2, Day9Part2::day9_part2),
+    (10, 1, Day10Part1::day10_part1),
+    (10, 2, Day10Part2::day10_part2),
+    (11, 1, Day11Part1::day11_part1),
+    (11, 2, Day11Part2::day11_part2),
+    (12, 1, Day12Part1::day12_part1),
+    (12, 2, Day12Part2::day12_part2),
+];
+
+/// Finds the registered solution for a day and part
+#[must_use]
+pub fn find(day: u32, part: u32) -> Option<&'static Solution> {
+    SOLUTIONS
+        .iter()
+        .find(|solution| solution.day == day && solution.part == part)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_day_has_both_parts() {
+        for day in 1..=12 {
+            for part in 1..=2 {
+                assert!(find(day, part).is_some(), "day {day} part {part}");
+            }
+        }
+    }
+
+    #[test]
+    fn runs_through_the_registration() -> Result<()> {
+        let answer = find(6, 1)
+            .expect("day 6 part 1 is registered")
+            .run("Time:      7  15   30\nDistance:  9  40  200\n")?;
+
+        assert_eq!("288", answer.value);
+        Ok(())
+    }
+
+    #[test]
+    fn generator_errors_are_reported() {
+        let error = find(6, 1)
+            .expect("day 6 part 1 is registered")
+            .run("not a race")
+            .unwrap_err();
+
+        assert_eq!("Day 6 - Part 1 failed while generating", error.to_string());
+    }
+}
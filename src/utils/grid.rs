@@ -0,0 +1,356 @@
+use std::{
+    fmt::{Debug, Display, Formatter},
+    ops::{Index, IndexMut},
+};
+
+use color_eyre::{
+    eyre::{ensure, eyre},
+    Result,
+};
+
+use crate::utils::point::Point;
+
+/// A rectangular grid of cells stored row by row, addressed by `Point`s where `x` is the
+/// column and `y` is the row
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid from text, converting each character with `cell`.
+    ///
+    /// # Errors
+    ///
+    /// Fails when `cell` rejects a character or the lines differ in length.
+    pub fn parse<F>(input: &str, mut cell: F) -> Result<Self>
+    where
+        F: FnMut(char) -> Result<T>,
+    {
+        input
+            .lines()
+            .map(|line| line.chars().map(&mut cell).collect::<Result<Vec<_>>>())
+            .collect::<Result<Vec<_>>>()?
+            .try_into()
+    }
+
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The bottom right corner of the grid, or `None` when it is empty
+    #[must_use]
+    pub fn max_point(&self) -> Option<Point> {
+        let x = i64::try_from(self.width.checked_sub(1)?).ok()?;
+        let y = i64::try_from(self.height.checked_sub(1)?).ok()?;
+
+        Some(Point { x, y })
+    }
+
+    fn offset(&self, point: Point) -> Option<usize> {
+        let x = usize::try_from(point.x).ok()?;
+        let y = usize::try_from(point.y).ok()?;
+
+        (x < self.width && y < self.height).then_some(y * self.width + x)
+    }
+
+    fn point(&self, offset: usize) -> Point {
+        let x = i64::try_from(offset % self.width).expect("grid fits in i64 coordinates");
+        let y = i64::try_from(offset / self.width).expect("grid fits in i64 coordinates");
+
+        Point { x, y }
+    }
+
+    #[must_use]
+    pub fn contains(&self, point: Point) -> bool {
+        self.offset(point).is_some()
+    }
+
+    #[must_use]
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.offset(point).map(|offset| &self.cells[offset])
+    }
+
+    #[must_use]
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.offset(point).map(|offset| &mut self.cells[offset])
+    }
+
+    /// Every point on the grid, row by row
+    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        (0..self.cells.len()).map(|offset| self.point(offset))
+    }
+
+    /// Every cell alongside its point, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(offset, cell)| (self.point(offset), cell))
+    }
+
+    #[must_use]
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        (y < self.height).then(|| &self.cells[y * self.width..(y + 1) * self.width])
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// The cells in column `x`, top to bottom. Empty when `x` is off the grid.
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        let cells = if x < self.width {
+            &self.cells[x..]
+        } else {
+            &[]
+        };
+
+        cells.iter().step_by(self.width.max(1))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    fn on_grid<'a>(
+        &'a self,
+        points: impl IntoIterator<Item = Point> + 'a,
+    ) -> impl Iterator<Item = (Point, &'a T)> {
+        points
+            .into_iter()
+            .filter_map(|point| self.get(point).map(|cell| (point, cell)))
+    }
+
+    /// The up to four on-grid cells sharing an edge with `point`
+    pub fn neighbors4(&self, point: Point) -> impl Iterator<Item = (Point, &T)> {
+        self.on_grid(point.cardinal_neighbors())
+    }
+
+    /// The up to eight on-grid cells sharing an edge or a corner with `point`
+    pub fn neighbors8(&self, point: Point) -> impl Iterator<Item = (Point, &T)> {
+        self.on_grid(point.neighbors())
+    }
+
+    #[must_use]
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    #[must_use]
+    pub fn filled(width: usize, height: usize, cell: T) -> Self {
+        Self {
+            cells: vec![cell; width * height],
+            width,
+            height,
+        }
+    }
+
+    /// Inserts a row of `cell`s so that it becomes row `y`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `y` is greater than the height of the grid.
+    pub fn insert_row(&mut self, y: usize, cell: T) {
+        assert!(y <= self.height, "row {y} is outside the grid");
+
+        let at = y * self.width;
+        self.cells
+            .splice(at..at, std::iter::repeat_n(cell, self.width));
+        self.height += 1;
+    }
+
+    /// Inserts a column of `cell`s so that it becomes column `x`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is greater than the width of the grid.
+    pub fn insert_column(&mut self, x: usize, cell: T) {
+        assert!(x <= self.width, "column {x} is outside the grid");
+
+        if self.width == 0 {
+            // There are no rows to split, just a new cell for each of them
+            self.cells = vec![cell; self.height];
+        } else {
+            let mut cells = Vec::with_capacity(self.cells.len() + self.height);
+            for row in self.cells.chunks(self.width) {
+                cells.extend_from_slice(&row[..x]);
+                cells.push(cell.clone());
+                cells.extend_from_slice(&row[x..]);
+            }
+            self.cells = cells;
+        }
+        self.width += 1;
+    }
+}
+
+impl<T> TryFrom<Vec<Vec<T>>> for Grid<T> {
+    type Error = color_eyre::Report;
+
+    fn try_from(rows: Vec<Vec<T>>) -> std::result::Result<Self, Self::Error> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+
+        if let Some((y, row)) = rows.iter().enumerate().find(|(_, row)| row.len() != width) {
+            return Err(eyre!(
+                "row {y} has {} cells but the first row has {width}",
+                row.len()
+            ));
+        }
+        ensure!(
+            i64::try_from(width).is_ok() && i64::try_from(height).is_ok(),
+            "grid is too large to address with points"
+        );
+
+        Ok(Self {
+            cells: rows.into_iter().flatten().collect(),
+            width,
+            height,
+        })
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, index: Point) -> &Self::Output {
+        self.get(index)
+            .unwrap_or_else(|| panic!("{index:?} is outside the grid"))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, index: Point) -> &mut Self::Output {
+        self.get_mut(index)
+            .unwrap_or_else(|| panic!("{index:?} is outside the grid"))
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: Debug> Debug for Grid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.rows()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use color_eyre::Result;
+    use indoc::indoc;
+
+    use super::*;
+
+    const SAMPLE: &str = indoc! {
+        "123
+         456"
+    };
+
+    fn digits(input: &str) -> Result<Grid<u32>> {
+        Grid::parse(input, |c| {
+            c.to_digit(10).ok_or_else(|| eyre!("{c} is not a digit"))
+        })
+    }
+
+    #[test]
+    fn parse_and_display() -> Result<()> {
+        let grid = digits(SAMPLE)?;
+
+        assert_eq!((3, 2), (grid.width(), grid.height()));
+        assert_eq!(Some(Point { x: 2, y: 1 }), grid.max_point());
+        assert_eq!(format!("{SAMPLE}\n"), grid.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_rejects_ragged_rows() {
+        assert!(digits("123\n45").is_err());
+        assert!(digits("1a3").is_err());
+    }
+
+    #[test]
+    fn bounds_checked_indexing() -> Result<()> {
+        let mut grid = digits(SAMPLE)?;
+
+        assert_eq!(6, grid[Point { x: 2, y: 1 }]);
+        assert_eq!(None, grid.get(Point { x: 3, y: 0 }));
+        assert_eq!(None, grid.get(Point { x: -1, y: 0 }));
+
+        grid[Point { x: 0, y: 0 }] = 9;
+        assert_eq!(Some(&9), grid.get(Point { x: 0, y: 0 }));
+        Ok(())
+    }
+
+    #[test]
+    fn rows_and_columns() -> Result<()> {
+        let grid = digits(SAMPLE)?;
+
+        assert_eq!(Some(&[4, 5, 6][..]), grid.row(1));
+        assert_eq!(vec![2, 5], grid.column(1).copied().collect::<Vec<_>>());
+        assert_eq!(3, grid.columns().count());
+        assert_eq!(0, grid.column(3).count());
+        Ok(())
+    }
+
+    #[test]
+    fn neighbors_stay_on_grid() -> Result<()> {
+        let grid = digits(SAMPLE)?;
+        let corner = Point { x: 0, y: 0 };
+
+        let cardinal: Vec<_> = grid.neighbors4(corner).map(|(_, &cell)| cell).collect();
+        let all: Vec<_> = grid.neighbors8(corner).map(|(_, &cell)| cell).collect();
+
+        assert_eq!(vec![2, 4], cardinal);
+        assert_eq!(vec![2, 4, 5], all);
+        Ok(())
+    }
+
+    #[test]
+    fn insert_rows_and_columns() -> Result<()> {
+        let mut grid = digits(SAMPLE)?;
+
+        grid.insert_row(1, 0);
+        grid.insert_column(3, 0);
+
+        assert_eq!("1230\n0000\n4560\n", grid.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn insert_column_into_an_empty_grid() {
+        let mut grid = Grid::filled(0, 2, 0);
+
+        grid.insert_column(0, 7);
+
+        assert_eq!(7, grid[Point::new(0, 1)]);
+        assert_eq!("7\n7\n", grid.to_string());
+    }
+}
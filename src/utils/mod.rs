@@ -1,3 +1,2 @@
+pub mod grid;
 pub mod point;
-
-
@@ -1,4 +1,8 @@
-use std::ops::Add;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use color_eyre::{eyre::eyre, Report};
+
+/// A position on a grid where `y` grows downwards, so north is `y - 1`
 #[derive(Copy, Clone, Debug, Default, PartialOrd, PartialEq, Eq, Hash)]
 pub struct Point {
     pub x: i64,
@@ -6,21 +10,33 @@ pub struct Point {
 }
 
 impl Point {
+    #[must_use]
+    pub const fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    /// All eight surrounding points, cardinal ones first
+    #[must_use]
     pub fn neighbors(&self) -> Vec<Self> {
-        let modifiers = [
-            (-1, 0).into(),
-            (1, 0).into(),
-            (0, -1).into(),
-            (0, 1).into(),
-            (-1, -1).into(),
-            (-1, 1).into(),
-            (1, -1).into(),
-            (1, 1).into(),
-        ];
+        self.cardinal_neighbors()
+            .into_iter()
+            .chain(self.diagonal_neighbors())
+            .collect()
+    }
 
-        modifiers.iter().map(|shift| *self + *shift).collect()
+    /// The four points sharing an edge with this one, clockwise from north
+    #[must_use]
+    pub fn cardinal_neighbors(&self) -> [Self; 4] {
+        Direction::ALL.map(|direction| *self + direction.offset())
     }
 
+    /// The four points sharing only a corner with this one, clockwise from north east
+    #[must_use]
+    pub fn diagonal_neighbors(&self) -> [Self; 4] {
+        Direction::ALL.map(|direction| *self + direction.offset() + direction.turn_right().offset())
+    }
+
+    #[must_use]
     pub fn constrain(&self, min: &Point, max: &Point) -> Self {
         Self {
             x: std::cmp::min(std::cmp::max(min.x, self.x), max.x),
@@ -28,12 +44,20 @@ impl Point {
         }
     }
 
+    #[must_use]
     pub fn is_valid(&self, min: &Point, max: &Point) -> bool {
         self.x >= min.x && self.y >= min.y && self.x <= max.x && self.y <= max.y
     }
 
+    #[must_use]
     pub fn manhattan_distance(&self, other: &Point) -> u64 {
-        ((self.x - other.x).abs() + (self.y - other.y).abs()) as u64
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// The number of king moves between the points, where diagonal steps are allowed
+    #[must_use]
+    pub fn chebyshev_distance(&self, other: &Point) -> u64 {
+        std::cmp::max(self.x.abs_diff(other.x), self.y.abs_diff(other.y))
     }
 }
 
@@ -48,6 +72,51 @@ impl Add<Point> for Point {
     }
 }
 
+impl AddAssign<Point> for Point {
+    fn add_assign(&mut self, rhs: Point) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub<Point> for Point {
+    type Output = Point;
+
+    fn sub(self, rhs: Point) -> Self::Output {
+        Self {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+        }
+    }
+}
+
+impl SubAssign<Point> for Point {
+    fn sub_assign(&mut self, rhs: Point) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            x: -self.x,
+            y: -self.y,
+        }
+    }
+}
+
+impl Mul<i64> for Point {
+    type Output = Point;
+
+    fn mul(self, rhs: i64) -> Self::Output {
+        Self {
+            x: self.x * rhs,
+            y: self.y * rhs,
+        }
+    }
+}
+
 impl From<(i64, i64)> for Point {
     fn from(value: (i64, i64)) -> Self {
         Point {
@@ -56,3 +125,152 @@ impl From<(i64, i64)> for Point {
         }
     }
 }
+
+impl From<glam::I64Vec2> for Point {
+    fn from(value: glam::I64Vec2) -> Self {
+        Point {
+            x: value.x,
+            y: value.y,
+        }
+    }
+}
+
+impl From<Point> for glam::I64Vec2 {
+    fn from(value: Point) -> Self {
+        glam::I64Vec2::new(value.x, value.y)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    /// Every direction, clockwise from north
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    /// The one step move in this direction
+    #[must_use]
+    pub fn offset(self) -> Point {
+        match self {
+            Direction::North => Point::new(0, -1),
+            Direction::East => Point::new(1, 0),
+            Direction::South => Point::new(0, 1),
+            Direction::West => Point::new(-1, 0),
+        }
+    }
+
+    /// Turns 90 degrees clockwise
+    #[must_use]
+    pub fn turn_right(self) -> Self {
+        match self {
+            Direction::North => Direction::East,
+            Direction::East => Direction::South,
+            Direction::South => Direction::West,
+            Direction::West => Direction::North,
+        }
+    }
+
+    /// Turns 90 degrees counter clockwise
+    #[must_use]
+    pub fn turn_left(self) -> Self {
+        self.turn_right().reverse()
+    }
+
+    #[must_use]
+    pub fn reverse(self) -> Self {
+        self.turn_right().turn_right()
+    }
+
+    /// Turns clockwise by `quarter_turns` 90 degree steps, counter clockwise when negative
+    #[must_use]
+    pub fn rotate(self, quarter_turns: i32) -> Self {
+        (0..quarter_turns.rem_euclid(4)).fold(self, |direction, _| direction.turn_right())
+    }
+}
+
+impl TryFrom<Point> for Direction {
+    type Error = Report;
+
+    fn try_from(value: Point) -> Result<Self, Self::Error> {
+        Direction::ALL
+            .into_iter()
+            .find(|direction| direction.offset() == value)
+            .ok_or_else(|| eyre!("offset is more than one step away or diagonal"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        let a = Point::new(3, -2);
+        let b = Point::new(1, 4);
+
+        assert_eq!(Point::new(4, 2), a + b);
+        assert_eq!(Point::new(2, -6), a - b);
+        assert_eq!(Point::new(-3, 2), -a);
+        assert_eq!(Point::new(9, -6), a * 3);
+    }
+
+    #[test]
+    fn distances() {
+        let a = Point::new(3, -2);
+        let b = Point::new(1, 4);
+
+        assert_eq!(8, a.manhattan_distance(&b));
+        assert_eq!(6, a.chebyshev_distance(&b));
+    }
+
+    #[test]
+    fn neighbor_sets() {
+        let origin = Point::default();
+
+        assert_eq!(
+            [(0, -1), (1, 0), (0, 1), (-1, 0)].map(Point::from),
+            origin.cardinal_neighbors()
+        );
+        assert_eq!(
+            [(1, -1), (1, 1), (-1, 1), (-1, -1)].map(Point::from),
+            origin.diagonal_neighbors()
+        );
+        assert_eq!(8, origin.neighbors().len());
+    }
+
+    #[test]
+    fn turning() {
+        assert_eq!(Direction::East, Direction::North.turn_right());
+        assert_eq!(Direction::West, Direction::North.turn_left());
+        assert_eq!(Direction::South, Direction::North.reverse());
+        assert_eq!(Direction::West, Direction::South.rotate(1));
+        assert_eq!(Direction::East, Direction::South.rotate(-1));
+        assert_eq!(Direction::South, Direction::South.rotate(8));
+    }
+
+    #[test]
+    fn direction_from_offset() {
+        for direction in Direction::ALL {
+            assert_eq!(direction, Direction::try_from(direction.offset()).unwrap());
+        }
+        assert!(Direction::try_from(Point::new(1, 1)).is_err());
+    }
+
+    #[test]
+    fn glam_round_trip() {
+        let point = Point::new(-5, 7);
+        let vec: glam::I64Vec2 = point.into();
+
+        assert_eq!(point, Point::from(vec));
+    }
+}
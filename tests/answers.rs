@@ -0,0 +1,98 @@
+use std::path::{Path, PathBuf};
+
+use aoc_rust_2023::runner;
+use color_eyre::{
+    eyre::{bail, eyre, WrapErr},
+    Result,
+};
+use toml::Table;
+
+fn manifest_dir() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Where the real inputs live, overridable with `AOC_INPUT_DIR`
+fn input_dir() -> PathBuf {
+    std::env::var_os("AOC_INPUT_DIR")
+        .map_or_else(|| manifest_dir().join("input/2023"), PathBuf::from)
+}
+
+fn load_answers(path: &Path) -> Result<Table> {
+    let manifest = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("could not read {}", path.display()))?;
+
+    manifest
+        .parse()
+        .wrap_err_with(|| format!("{} is not valid TOML", path.display()))
+}
+
+/// The recorded answer, which may be written as a string or, for numbers, an integer
+fn expected_answer(answers: &Table, day: u32, part: u32) -> Result<Option<String>> {
+    let Some(day_answers) = answers.get(&format!("day{day}")) else {
+        return Ok(None);
+    };
+    let day_answers = day_answers
+        .as_table()
+        .ok_or_else(|| eyre!("[day{day}] should be a table of parts"))?;
+
+    day_answers
+        .get(&format!("part{part}"))
+        .map(|answer| {
+            answer
+                .as_str()
+                .map(str::to_owned)
+                .or_else(|| answer.as_integer().map(|n| n.to_string()))
+                .ok_or_else(|| eyre!("day{day}.part{part} should be a string or an integer"))
+        })
+        .transpose()
+}
+
+#[test]
+fn recorded_answers_still_hold() -> Result<()> {
+    let answers = load_answers(&manifest_dir().join("answers.toml"))?;
+    let dir = input_dir();
+    let mut failures = vec![];
+
+    for solution in runner::SOLUTIONS {
+        let (day, part) = (solution.day, solution.part);
+        let path = dir.join(format!("day{day}.txt"));
+        if !path.exists() {
+            eprintln!(
+                "Day {day} - Part {part}: skipped, {} does not exist",
+                path.display()
+            );
+            continue;
+        }
+        let Some(expected) = expected_answer(&answers, day, part)? else {
+            eprintln!("Day {day} - Part {part}: skipped, no answer recorded");
+            continue;
+        };
+
+        let input = std::fs::read_to_string(&path)
+            .wrap_err_with(|| format!("could not read {}", path.display()))?;
+        match solution.run(&input) {
+            Ok(answer) if answer.value == expected => {}
+            Ok(answer) => failures.push(format!(
+                "Day {day} - Part {part}: expected {expected} but got {}",
+                answer.value
+            )),
+            Err(error) => failures.push(format!("{error:#}")),
+        }
+    }
+
+    if !failures.is_empty() {
+        bail!("{}", failures.join("\n"));
+    }
+    Ok(())
+}
+
+#[test]
+fn reads_answers_by_day_and_part() -> Result<()> {
+    let answers: Table = "[day6]\npart1 = \"288\"\npart2 = 71503\n".parse()?;
+
+    assert_eq!(Some("288".to_owned()), expected_answer(&answers, 6, 1)?);
+    assert_eq!(Some("71503".to_owned()), expected_answer(&answers, 6, 2)?);
+    assert_eq!(None, expected_answer(&answers, 7, 1)?);
+    assert!(expected_answer(&"[day6]\npart1 = true\n".parse()?, 6, 1).is_err());
+    Ok(())
+}